@@ -0,0 +1,207 @@
+//! looks at level files without opening the editor, for scripts and the build.
+//! exits with 1 when something is wrong with a level or two levels differ and 2 on bad arguments
+
+use std::path::Path;
+use std::process::ExitCode;
+
+use level_editor::entity::EntityKind;
+use level_editor::level::{read_file_version, Level, Medal, TEXT_EXTENSION};
+
+const USAGE: &str = "usage:
+    sgld info <level>             what is in a level
+    sgld validate <level>...      checks every level loads and has no errors
+    sgld convert <from> <to>      rewrites a level, `.sglt` is text and anything else binary
+    sgld diff <a> <b>             what changed between two levels";
+
+fn main() -> ExitCode {
+    let args = std::env::args().skip(1).collect::<Vec<String>>();
+    let args = args.iter().map(String::as_str).collect::<Vec<&str>>();
+
+    let result = match args.as_slice() {
+        ["info", path] => info(Path::new(path)),
+        ["validate", paths @ ..] if !paths.is_empty() => validate(paths),
+        ["convert", from, to] => convert(Path::new(from), Path::new(to)),
+        ["diff", a, b] => diff(Path::new(a), Path::new(b)),
+        _ => {
+            eprintln!("{}", USAGE);
+            return ExitCode::from(2);
+        },
+    };
+
+    match result {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::FAILURE,
+        Err(e) => {
+            eprintln!("{}", e);
+            ExitCode::FAILURE
+        },
+    }
+}
+
+fn load(path: &Path) -> Result<Level, String> {
+    Level::load(path).map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+fn info(path: &Path) -> Result<bool, String> {
+    let level = load(path)?;
+    let version = read_file_version(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    let format = if path.extension().is_some_and(|ext| ext == TEXT_EXTENSION) { "text" } else { "binary" };
+
+    println!("{}", path.display());
+    println!("  version: {} ({})", version, format);
+    println!("  hash: {:016x}", level.content_hash());
+
+    let meta = level.get_meta();
+    if !meta.name.is_empty() {
+        println!("  name: {}", meta.name);
+    }
+    if !meta.author.is_empty() {
+        println!("  author: {}", meta.author);
+    }
+    if let Some(difficulty) = meta.difficulty {
+        println!("  difficulty: {}", difficulty.name());
+    }
+    for medal in Medal::ALL {
+        if let Some(secs) = meta.get_medal_time(medal) {
+            println!("  {}: {}s", medal.name(), secs);
+        }
+    }
+
+    let start = level.get_player_start();
+    println!("  player start: {}, {}", start.x, start.y);
+    println!("  platforms: {}", level.get_platforms().len());
+
+    let kinds = [EntityKind::Goal, EntityKind::Spikes, EntityKind::KillPlane, EntityKind::Checkpoint]
+        .into_iter()
+        .map(|kind| (kind, level.get_entities().iter().filter(|e| e.kind == kind).count()))
+        .filter(|(_, count)| *count > 0)
+        .map(|(kind, count)| format!("{} {}", count, kind.name()))
+        .collect::<Vec<String>>();
+    if kinds.is_empty() {
+        println!("  entities: 0");
+    } else {
+        println!("  entities: {} ({})", level.get_entities().len(), kinds.join(", "));
+    }
+
+    if let Some(y) = level.get_death_y() {
+        let from = if level.get_stored_death_y().is_some() { "set in the level" } else { "worked out" };
+        println!("  death y: {} ({})", y, from);
+    }
+
+    let (min, max) = level.get_bounds();
+    println!("  bounds: {}, {} to {}, {} ({} x {})", min.x, min.y, max.x, max.y, max.x - min.x, max.y - min.y);
+
+    Ok(true)
+}
+
+/// warnings are printed but only errors fail
+fn validate(paths: &[&str]) -> Result<bool, String> {
+    let mut all_ok = true;
+
+    for path in paths {
+        let level = match Level::load(path) {
+            Ok(level) => level,
+            Err(e) => {
+                println!("{}: {}", path, e);
+                all_ok = false;
+                continue;
+            },
+        };
+
+        let diagnostics = level.validate();
+        for diagnostic in &diagnostics {
+            println!("{}: {}", path, diagnostic);
+        }
+
+        if diagnostics.iter().any(|d| d.is_error()) {
+            all_ok = false;
+        } else if diagnostics.is_empty() {
+            println!("{}: ok", path);
+        }
+    }
+
+    Ok(all_ok)
+}
+
+fn convert(from: &Path, to: &Path) -> Result<bool, String> {
+    let level = load(from)?;
+    level
+        .write_to_file(to)
+        .map_err(|e| format!("could not write {}: {}", to.display(), e))?;
+
+    Ok(true)
+}
+
+fn diff(a: &Path, b: &Path) -> Result<bool, String> {
+    let changes = diff_levels(&load(a)?, &load(b)?);
+    for line in &changes {
+        println!("{}", line);
+    }
+
+    Ok(changes.is_empty())
+}
+
+/// lines of the text format only in `a` start with `-` and ones only in `b` with `+`.
+/// platforms and entities are compared as a set so reordering them isnt a change
+fn diff_levels(a: &Level, b: &Level) -> Vec<String> {
+    let a_text = a.to_text();
+    let b_text = b.to_text();
+    let mut a_lines = content_lines(&a_text);
+    let mut b_lines = content_lines(&b_text);
+
+    // whats left in both after pairing off matches is the difference
+    a_lines.retain(|line| match b_lines.iter().position(|l| l == line) {
+        Some(idx) => {
+            b_lines.remove(idx);
+            false
+        },
+        None => true,
+    });
+
+    a_lines
+        .into_iter()
+        .map(|l| format!("- {}", l))
+        .chain(b_lines.into_iter().map(|l| format!("+ {}", l)))
+        .collect()
+}
+
+fn content_lines(text: &str) -> Vec<&str> {
+    text
+        .lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty() && !l.starts_with('#'))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bottomless_pit::vec2;
+    use level_editor::entity::Entity;
+    use level_editor::level::Platform;
+
+    #[test]
+    fn diff_only_shows_what_changed() {
+        let mut a = Level::headless(vec![
+            Platform::new(vec2!(0.0, 600.0), vec2!(600.0, 50.0)),
+            Platform::new(vec2!(10.0, 200.0), vec2!(300.0, 100.0)),
+        ]);
+        a.add_entity(Entity::new(EntityKind::Goal, vec2!(0.0, 480.0), vec2!(80.0, 120.0)));
+
+        // same platforms in another order
+        let mut b = Level::headless(vec![
+            Platform::new(vec2!(10.0, 200.0), vec2!(300.0, 100.0)),
+            Platform::new(vec2!(0.0, 600.0), vec2!(600.0, 50.0)),
+        ]);
+        b.add_entity(Entity::new(EntityKind::Goal, vec2!(0.0, 480.0), vec2!(80.0, 120.0)));
+        assert!(diff_levels(&a, &b).is_empty());
+
+        b.set_player_start(vec2!(5.0, 6.0));
+        b.get_mut_meta().name = String::from("moved");
+        assert_eq!(diff_levels(&a, &b), vec![
+            "- player_start = 0, 0",
+            "+ player_start = 5, 6",
+            "+ name = moved",
+        ]);
+    }
+}
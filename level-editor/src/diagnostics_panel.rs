@@ -0,0 +1,153 @@
+use std::fmt::Debug;
+
+use bottomless_pit::colour::Colour;
+use bottomless_pit::engine_handle::Engine;
+use bottomless_pit::material::{Material, MaterialBuilder};
+use bottomless_pit::render::RenderInformation;
+use bottomless_pit::text::TextMaterial;
+use bottomless_pit::vec2;
+use bottomless_pit::vectors::Vec2;
+
+use crate::level::{Diagnostic, Level, Severity, Subject, PLAYER_SIZE};
+
+// under the level info panel so both can be open
+const PANEL_POS: Vec2<f32> = vec2!(20.0, 260.0);
+const ROW_SIZE: Vec2<f32> = vec2!(460.0, 24.0);
+const ROW_GAP: f32 = 2.0;
+// rows past this get rolled into an "and n more" line
+const MAX_ROWS: usize = 8;
+// how far the highlight sticks out past what its highlighting, so zero size platforms still show
+const HIGHLIGHT_GROWTH: f32 = 4.0;
+
+/// lists what `Level::validate` found and highlights the parts of the level they are about.
+/// it checks again every frame so fixing something takes it off the list
+pub(crate) struct DiagnosticsPanel {
+    material: Material,
+    rows: Vec<TextMaterial>,
+    diagnostics: Vec<Diagnostic>,
+    // copied out of the level every update, its borrowed by the time the panel is drawn
+    // and the flagged parts can move without the list changing
+    highlights: Vec<(Vec2<f32>, Vec2<f32>, Colour)>,
+}
+
+impl DiagnosticsPanel {
+    pub fn new(level: &Level, engine: &mut Engine) -> Self {
+        let rows = (0..MAX_ROWS)
+            .map(|_| TextMaterial::new("", Colour::BLACK, 14.0, 18.0, engine))
+            .collect();
+
+        let mut panel = Self {
+            material: MaterialBuilder::new().build(engine),
+            rows,
+            diagnostics: level.validate(),
+            highlights: Vec::new(),
+        };
+        panel.refresh(engine);
+        panel.rebuild_highlights(level);
+
+        panel
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.diagnostics.is_empty()
+    }
+
+    pub fn has_errors(&self) -> bool {
+        self.diagnostics.iter().any(|d| d.is_error())
+    }
+
+    pub fn update(&mut self, level: &Level, engine: &mut Engine) {
+        let found = level.validate();
+        if found != self.diagnostics {
+            self.diagnostics = found;
+            self.refresh(engine);
+        }
+        self.rebuild_highlights(level);
+    }
+
+    fn refresh(&mut self, engine: &mut Engine) {
+        let mut lines = self.diagnostics.iter().map(|d| d.to_string()).collect::<Vec<String>>();
+        if lines.is_empty() {
+            lines.push(String::from("no problems found"));
+        } else if lines.len() > MAX_ROWS {
+            let more = lines.len() - (MAX_ROWS - 1);
+            lines.truncate(MAX_ROWS - 1);
+            lines.push(format!("and {} more", more));
+        }
+
+        for (idx, row) in self.rows.iter_mut().enumerate() {
+            row.set_text(lines.get(idx).map_or("", String::as_str), Colour::BLACK, engine);
+            row.prepare(engine);
+        }
+    }
+
+    fn rebuild_highlights(&mut self, level: &Level) {
+        self.highlights = self.diagnostics
+            .iter()
+            .filter_map(|d| {
+                let (pos, size) = subject_rect(level, d.subject)?;
+                let values = [pos.x, pos.y, size.x, size.y];
+                values.iter().all(|v| v.is_finite()).then(|| {
+                    // a negative size grows back from pos so the rect starts at the min corner
+                    let pos = vec2!(
+                        pos.x + size.x.min(0.0) - HIGHLIGHT_GROWTH,
+                        pos.y + size.y.min(0.0) - HIGHLIGHT_GROWTH
+                    );
+                    let size = vec2!(size.x.abs() + HIGHLIGHT_GROWTH * 2.0, size.y.abs() + HIGHLIGHT_GROWTH * 2.0);
+                    (pos, size, severity_colour(d.severity, 0.5))
+                })
+            })
+            .collect();
+    }
+
+    pub fn draw<'p, 'o>(&'o mut self, renderer: &mut RenderInformation<'p, 'o>) where 'o: 'p {
+        for (pos, size, colour) in &self.highlights {
+            self.material.add_rectangle(*pos, *size, *colour, renderer);
+        }
+
+        let shown = self.diagnostics.len().clamp(1, MAX_ROWS);
+        let more_row = (self.diagnostics.len() > MAX_ROWS).then_some(MAX_ROWS - 1);
+        for idx in 0..shown {
+            let colour = match self.diagnostics.get(idx) {
+                Some(d) if more_row != Some(idx) => severity_colour(d.severity, 0.9),
+                _ => Colour::from_rgba(230.0, 230.0, 230.0, 0.9),
+            };
+            self.material.add_rectangle(row_pos(idx), ROW_SIZE, colour, renderer);
+        }
+        self.material.draw(renderer);
+
+        for (idx, row) in self.rows.iter_mut().enumerate().take(shown) {
+            row.add_instance(row_pos(idx) + vec2!(6.0, 3.0), Colour::WHITE, renderer);
+            row.draw(renderer);
+        }
+    }
+}
+
+impl Debug for DiagnosticsPanel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f
+            .debug_struct("DiagnosticsPanel")
+            .field("diagnostics", &self.diagnostics)
+            .finish()
+    }
+}
+
+fn subject_rect(level: &Level, subject: Subject) -> Option<(Vec2<f32>, Vec2<f32>)> {
+    match subject {
+        Subject::Level => None,
+        Subject::PlayerStart => Some((level.get_player_start(), PLAYER_SIZE)),
+        Subject::Platform(idx) => level.get_platforms().get(idx).map(|p| (p.pos, p.size)),
+        Subject::Entity(idx) => level.get_entities().get(idx).map(|e| (e.pos, e.size)),
+    }
+}
+
+fn severity_colour(severity: Severity, alpha: f32) -> Colour {
+    match severity {
+        Severity::Error => Colour::from_rgba(240.0, 90.0, 90.0, alpha),
+        Severity::Warning => Colour::from_rgba(250.0, 210.0, 80.0, alpha),
+    }
+}
+
+fn row_pos(idx: usize) -> Vec2<f32> {
+    vec2!(PANEL_POS.x, PANEL_POS.y + idx as f32 * (ROW_SIZE.y + ROW_GAP))
+}
@@ -11,8 +11,11 @@ use bottomless_pit::engine_handle::Engine;
 use bottomless_pit::render::RenderInformation;
 use bottomless_pit::vectors::Vec2;
 use utils::ui::button::{Button, CallBackButton};
-use crate::level::{Level, Platform};
-use crate::tools::{MoveTool, PlatformTool, Selector, Tool};
+use crate::entity::EntityKind;
+use crate::level::{Level, LevelFormat, Platform, PLAYER_SIZE};
+use crate::diagnostics_panel::DiagnosticsPanel;
+use crate::meta_panel::MetaPanel;
+use crate::tools::{EntityTool, FrictionTool, MoveTool, PlatformTool, PlayerStartTool, Selector, Tool};
 
 
 #[derive(Debug)]
@@ -25,34 +28,83 @@ impl EditorWithState<Editing> {
     fn update(&mut self, engine: &mut Engine) -> Event {
         let mouse_pos = engine.get_mouse_position();
 
-        if self.state.current_tool.can_switch() {
-            self.change_tool(engine);
+        // I opens and closes the level info panel, tools are put down while its open
+        let typing = self.state.meta_panel.as_ref().is_some_and(|p| p.is_typing());
+        if !typing && self.state.current_tool.can_switch() && engine.is_key_pressed(Key::I) {
+            self.state.meta_panel = match self.state.meta_panel {
+                Some(_) => None,
+                None => Some(MetaPanel::new(self.state.context.get_level().get_meta(), engine)),
+            };
         }
 
-        if engine.is_mouse_key_pressed(MouseKey::Left) {
-            self.state.current_tool.on_click(mouse_pos, &mut self.state.context);
-        } else if engine.is_mouse_key_released(MouseKey::Left) {
-            self.state.current_tool.on_mouse_release(mouse_pos, &mut self.state.context);
+        // V shows whats wrong with the level, tools keep working while its open
+        if !typing && self.state.current_tool.can_switch() && engine.is_key_pressed(Key::V) {
+            self.state.diagnostics_panel = match self.state.diagnostics_panel {
+                Some(_) => None,
+                None => Some(DiagnosticsPanel::new(self.state.context.get_level(), engine)),
+            };
         }
 
-        self.state.current_tool.update(engine, &mut self.state.context);
+        if let Some(panel) = &mut self.state.meta_panel {
+            panel.update(self.state.context.get_mut_level().get_mut_meta(), engine);
+        } else {
+            self.update_tool(mouse_pos, engine);
+        }
 
+        // ctrl s saves binary, ctrl shift s saves text
         if engine.check_modifiers(ModifierKeys::Ctrl) && engine.is_key_pressed(Key::S) {
-            let working_dir = env::current_dir().unwrap();
+            let format = if engine.check_modifiers(ModifierKeys::Shift) {
+                LevelFormat::Text
+            } else {
+                LevelFormat::Binary
+            };
+            self.save(format, engine);
+        }
 
-            let path = rfd::FileDialog::new()
-                .add_filter("Speed Game Level Data", &["sgld"])
-                .set_directory(working_dir)
-                .set_file_name("out.sgld")
-                .save_file();
+        if let Some(panel) = &mut self.state.diagnostics_panel {
+            panel.update(self.state.context.get_level(), engine);
+        }
 
-            if let Some(p) = path {
-                self.state.context.write_level_to_file(p).unwrap();
-            }
+        Event::None
+    }
+
+    /// checks the level first, warnings are shown but still saved and errors stop the save
+    fn save(&mut self, format: LevelFormat, engine: &mut Engine) {
+        let panel = DiagnosticsPanel::new(self.state.context.get_level(), engine);
+        let has_errors = panel.has_errors();
+        if !panel.is_empty() {
+            self.state.diagnostics_panel = Some(panel);
+        }
+        if has_errors {
+            return;
         }
 
+        let working_dir = env::current_dir().unwrap();
 
-        Event::None
+        let path = rfd::FileDialog::new()
+            .add_filter(format.description(), &[format.extension()])
+            .set_directory(working_dir)
+            .set_file_name(format!("out.{}", format.extension()))
+            .save_file();
+
+        // the dialog lets any name through, the extension is what picks how its written
+        if let Some(p) = path {
+            self.state.context.write_level_to_file(format.fix_path(&p)).unwrap();
+        }
+    }
+
+    fn update_tool(&mut self, mouse_pos: Vec2<f32>, engine: &mut Engine) {
+        if self.state.current_tool.can_switch() {
+            self.change_tool(engine);
+        }
+
+        if engine.is_mouse_key_pressed(MouseKey::Left) {
+            self.state.current_tool.on_click(mouse_pos, &mut self.state.context);
+        } else if engine.is_mouse_key_released(MouseKey::Left) {
+            self.state.current_tool.on_mouse_release(mouse_pos, &mut self.state.context);
+        }
+
+        self.state.current_tool.update(engine, &mut self.state.context);
     }
 
     fn change_tool(&mut self, engine: &mut Engine) {
@@ -62,14 +114,35 @@ impl EditorWithState<Editing> {
             self.state.current_tool = Box::new(PlatformTool::new());
         } else if engine.is_key_pressed(Key::M) {
             self.state.current_tool = Box::new(MoveTool::new());
+        } else if engine.is_key_pressed(Key::F) {
+            self.state.current_tool = Box::new(FrictionTool::new());
+        } else if engine.is_key_pressed(Key::G) {
+            self.state.current_tool = Box::new(EntityTool::new(EntityKind::Goal));
+        } else if engine.is_key_pressed(Key::H) {
+            self.state.current_tool = Box::new(EntityTool::new(EntityKind::Spikes));
+        } else if engine.is_key_pressed(Key::K) {
+            self.state.current_tool = Box::new(EntityTool::new(EntityKind::KillPlane));
+        } else if engine.is_key_pressed(Key::C) {
+            self.state.current_tool = Box::new(EntityTool::new(EntityKind::Checkpoint));
+        } else if engine.is_key_pressed(Key::T) {
+            self.state.current_tool = Box::new(PlayerStartTool::new());
         }
     }
 
     fn render<'pass, 'others>(&'others mut self, mut renderer: RenderInformation<'pass, 'others>) where 'others: 'pass {
+        self.state.context.draw_player_start(&mut self.editor_mat, &renderer);
         self.state.current_tool.draw(&mut self.editor_mat, &mut self.state.context, &mut renderer);
         self.editor_mat.draw(&mut renderer);
 
-        self.state.context.render(renderer);
+        self.state.context.render(&mut renderer);
+
+        if let Some(panel) = &mut self.state.diagnostics_panel {
+            panel.draw(&mut renderer);
+        }
+
+        if let Some(panel) = &mut self.state.meta_panel {
+            panel.draw(&mut renderer);
+        }
     }
 }
 
@@ -117,6 +190,9 @@ trait CoolTool: Tool + Debug {}
 impl CoolTool for Selector {}
 impl CoolTool for PlatformTool {}
 impl CoolTool for MoveTool {}
+impl CoolTool for FrictionTool {}
+impl CoolTool for EntityTool {}
+impl CoolTool for PlayerStartTool {}
 
 #[derive(Debug)]
 struct Menu {
@@ -144,6 +220,10 @@ impl Menu {
 struct Editing {
     context: EditorContext,
     current_tool: Box<dyn CoolTool>,
+    // open while editing the level info
+    meta_panel: Option<MetaPanel>,
+    // open after saving a level with problems or pressing V
+    diagnostics_panel: Option<DiagnosticsPanel>,
 }
 
 #[derive(Debug)]
@@ -204,6 +284,8 @@ impl From<(Level, EditorWithState<Menu>)> for EditorWithState<Editing> {
             state: Editing {
                 context: EditorContext::new(level),
                 current_tool: Box::new(Selector::new()),
+                meta_panel: None,
+                diagnostics_panel: None,
             },
             editor_mat: value.editor_mat,
         }
@@ -283,11 +365,17 @@ impl EditorContext {
         &self.level
     }
 
-    fn render<'pass, 'others>(&'others mut self, mut renderer: RenderInformation<'pass, 'others>)
+    /// a player sized box where the level spawns them
+    fn draw_player_start(&self, material: &mut Material, renderer: &RenderInformation) {
+        let colour = Colour::from_rgba(255.0, 170.0, 0.0, 0.5);
+        material.add_rectangle(self.level.get_player_start(), PLAYER_SIZE, colour, renderer);
+    }
+
+    fn render<'pass, 'others>(&'others mut self, renderer: &mut RenderInformation<'pass, 'others>)
     where
         'others: 'pass {
 
-        self.level.draw(&mut renderer);
+        self.level.draw(renderer);
     }
 }
 
@@ -0,0 +1,148 @@
+use bottomless_pit::colour::Colour;
+use bottomless_pit::material::Material;
+use bottomless_pit::render::RenderInformation;
+use bottomless_pit::vec2;
+use bottomless_pit::vectors::Vec2;
+
+use utils::collision;
+
+/// bytes taken by one entity in a level file, kind + pos + size
+pub(crate) const ENTITY_RECORD_LEN: usize = 17;
+
+/// what an entity does when the player touches it
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EntityKind {
+    /// finishes the level
+    Goal,
+    /// kills on touch
+    Spikes,
+    /// kills anything that falls bellow its top edge, across the whole level not just its width
+    KillPlane,
+    /// where the player comes back after dying once its been touched
+    Checkpoint,
+}
+
+impl EntityKind {
+    pub(crate) fn to_byte(self) -> u8 {
+        match self {
+            Self::Goal => 0,
+            Self::Spikes => 1,
+            Self::KillPlane => 2,
+            Self::Checkpoint => 3,
+        }
+    }
+
+    pub(crate) fn from_byte(byte: u8) -> Option<Self> {
+        match byte {
+            0 => Some(Self::Goal),
+            1 => Some(Self::Spikes),
+            2 => Some(Self::KillPlane),
+            3 => Some(Self::Checkpoint),
+            _ => None,
+        }
+    }
+
+    pub fn colour(&self) -> Colour {
+        match self {
+            Self::Goal => Colour::from_rgba(80.0, 220.0, 120.0, 0.6),
+            Self::Spikes => Colour::from_rgba(230.0, 60.0, 60.0, 0.9),
+            Self::KillPlane => Colour::from_rgba(140.0, 20.0, 20.0, 0.5),
+            Self::Checkpoint => Colour::from_rgba(80.0, 150.0, 240.0, 0.5),
+        }
+    }
+
+    /// the name used in `.sglt` files
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Goal => "goal",
+            Self::Spikes => "spikes",
+            Self::KillPlane => "kill_plane",
+            Self::Checkpoint => "checkpoint",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        [Self::Goal, Self::Spikes, Self::KillPlane, Self::Checkpoint]
+            .into_iter()
+            .find(|k| k.name() == name)
+    }
+
+    pub fn is_deadly(&self) -> bool {
+        matches!(self, Self::Spikes | Self::KillPlane)
+    }
+}
+
+/// a zone in the level that isnt solid but does something when touched
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Entity {
+    pub kind: EntityKind,
+    pub pos: Vec2<f32>,
+    pub size: Vec2<f32>,
+}
+
+impl Entity {
+    pub fn new(kind: EntityKind, pos: Vec2<f32>, size: Vec2<f32>) -> Self {
+        Self {
+            kind,
+            pos,
+            size,
+        }
+    }
+
+    pub fn from_corners(kind: EntityKind, c1: Vec2<f32>, c2: Vec2<f32>) -> Self {
+        let size = c1 - c2;
+        let size = vec2!(size.x.abs(), size.y.abs());
+        let pos = vec2!(c1.x.min(c2.x), c1.y.min(c2.y));
+
+        Self {
+            kind,
+            pos,
+            size,
+        }
+    }
+
+    /// where something `size` big stands on the bottom of the entity, left aligned.
+    /// used to respawn the player at a checkpoint
+    pub fn standing_pos(&self, size: Vec2<f32>) -> Vec2<f32> {
+        vec2!(self.pos.x, self.pos.y + self.size.y - size.y)
+    }
+
+    /// true if a rectangle is inside or overlapping the entity
+    pub fn touches(&self, other_pos: Vec2<f32>, other_size: Vec2<f32>) -> bool {
+        collision::rect_overlaps(self.pos, self.size, other_pos, other_size)
+    }
+
+    pub fn draw(&self, mat: &mut Material, renderer: &RenderInformation) {
+        mat.add_rectangle(self.pos, self.size, self.kind.colour(), renderer);
+    }
+
+    pub(crate) fn to_bytes(self) -> [u8; ENTITY_RECORD_LEN] {
+        let mut bytes = [0; ENTITY_RECORD_LEN];
+        bytes[0] = self.kind.to_byte();
+        bytes[1..5].copy_from_slice(&self.pos.x.to_le_bytes());
+        bytes[5..9].copy_from_slice(&self.pos.y.to_le_bytes());
+        bytes[9..13].copy_from_slice(&self.size.x.to_le_bytes());
+        bytes[13..17].copy_from_slice(&self.size.y.to_le_bytes());
+
+        bytes
+    }
+
+    /// None if the kind byte isnt one we know about or the numbers cant be a real zone
+    pub(crate) fn from_le_bytes(bytes: &[u8; ENTITY_RECORD_LEN]) -> Option<Self> {
+        let kind = EntityKind::from_byte(bytes[0])?;
+        let f = |i: usize| f32::from_le_bytes([bytes[i], bytes[i + 1], bytes[i + 2], bytes[i + 3]]);
+        let entity = Self {
+            kind,
+            pos: vec2!(f(1), f(5)),
+            size: vec2!(f(9), f(13)),
+        };
+
+        entity.is_sensible().then_some(entity)
+    }
+
+    /// no NaNs, infinities or negative sizes
+    pub(crate) fn is_sensible(&self) -> bool {
+        let values = [self.pos.x, self.pos.y, self.size.x, self.size.y];
+        values.iter().all(|v| v.is_finite()) && self.size.x >= 0.0 && self.size.y >= 0.0
+    }
+}
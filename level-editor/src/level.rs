@@ -1,6 +1,7 @@
-use std::fs::{File, OpenOptions};
-use std::path::Path;
-use std::io::{self, BufReader, Read, Write};
+use std::fmt::Display;
+use std::fs::OpenOptions;
+use std::path::{Path, PathBuf};
+use std::io::{self, Write};
 
 use bottomless_pit::colour::Colour;
 use bottomless_pit::material::Material;
@@ -9,34 +10,165 @@ use bottomless_pit::vec2;
 use bottomless_pit::vectors::Vec2;
 
 use utils::collision;
+use utils::config::ConfigError;
+
+use crate::entity::{Entity, EntityKind, ENTITY_RECORD_LEN};
+use utils::hash::fnv1a;
+
+mod meta;
+mod text;
+mod validate;
+
+pub use meta::{Difficulty, LevelMeta, Medal};
+pub use validate::{Diagnostic, Severity, Subject};
 
 // SGLD in bytes
 const FILE_HEADER: [u8; 4] = [115, 103, 108, 100];
+const FILE_VERSION: u16 = 3;
+/// files with this extension are saved and loaded as text instead of binary
+pub const TEXT_EXTENSION: &str = "sglt";
+const BINARY_EXTENSION: &str = "sgld";
+const PLATFORM_RECORD_LEN: usize = 20;
+
+// chunk tags for version 3 files, see readme.md
+const CHUNK_PLAYER_START: [u8; 4] = *b"STRT";
+const CHUNK_PLATFORMS: [u8; 4] = *b"PLAT";
+const CHUNK_ENTITIES: [u8; 4] = *b"ENTS";
+const CHUNK_META: [u8; 4] = *b"META";
+const CHUNK_DEATH_Y: [u8; 4] = *b"DTHY";
+const CHUNK_END: [u8; 4] = *b"END ";
+
+// how far under the lowest platform the player can fall before dying when there is no kill plane
+pub const DEATH_MARGIN: f32 = 1000.0;
+
+/// the players hitbox, kept here so the editor can show where they spawn
+pub const PLAYER_SIZE: Vec2<f32> = vec2!(96.0, 114.0);
+
+pub const ICE_FRICTION: f32 = 0.2;
+pub const NORMAL_FRICTION: f32 = 1.0;
+pub const STICKY_FRICTION: f32 = 2.5;
 
 #[derive(Debug)]
 pub struct Level {
-    platform_material: Material,
+    // None when running without a window, nothing gets drawn then
+    platform_material: Option<Material>,
     inner: InnerLevel
 }
 
 impl Level {
     pub fn new(platforms: Vec<Platform>, platform_material: Material) -> Self {
         Self {
-            platform_material,
+            platform_material: Some(platform_material),
+            inner: InnerLevel::new(platforms),
+        }
+    }
+
+    /// a level with no material, for simulating without an `Engine`
+    pub fn headless(platforms: Vec<Platform>) -> Self {
+        Self {
+            platform_material: None,
             inner: InnerLevel::new(platforms),
         }
     }
 
+    /// reads a level saved by the editor, `.sglt` files are read as text. it comes back
+    /// headless, give it a material with `set_platform_mat` to draw it
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, LevelLoadError> {
+        let inner = if is_text_path(path.as_ref()) {
+            InnerLevel::from_text(&std::fs::read_to_string(path)?)?
+        } else {
+            InnerLevel::read_from_file(path)?
+        };
+
+        Ok(Self {
+            platform_material: None,
+            inner,
+        })
+    }
+
     pub fn get_platforms(&self) -> &[Platform] {
         self.inner.get_platforms()
     }
 
+    pub fn get_entities(&self) -> &[Entity] {
+        self.inner.get_entities()
+    }
+
+    /// top left of the player when they spawn
+    pub fn get_player_start(&self) -> Vec2<f32> {
+        self.inner.player_start
+    }
+
+    pub fn set_player_start(&mut self, pos: Vec2<f32>) {
+        self.inner.player_start = pos;
+    }
+
+    pub fn get_meta(&self) -> &LevelMeta {
+        &self.inner.meta
+    }
+
+    pub fn get_mut_meta(&mut self) -> &mut LevelMeta {
+        &mut self.inner.meta
+    }
+
+    /// the death line the level was saved with, None to work it out from the level
+    pub fn get_stored_death_y(&self) -> Option<f32> {
+        self.inner.death_y
+    }
+
+    pub fn set_death_y(&mut self, death_y: Option<f32>) {
+        self.inner.death_y = death_y;
+    }
+
+    /// the top left and bottom right corners of everything in the level, including the player
+    /// where they spawn
+    pub fn get_bounds(&self) -> (Vec2<f32>, Vec2<f32>) {
+        let start = self.get_player_start();
+        let rects = self.get_platforms()
+            .iter()
+            .map(|p| (p.pos, p.size))
+            .chain(self.get_entities().iter().map(|e| (e.pos, e.size)));
+
+        rects.fold((start, start + PLAYER_SIZE), |(min, max), (pos, size)| {
+            (
+                vec2!(min.x.min(pos.x), min.y.min(pos.y)),
+                vec2!(max.x.max(pos.x + size.x), max.y.max(pos.y + size.y)),
+            )
+        })
+    }
+
+    /// anything falling bellow this dies. its the highest of the levels stored death y and
+    /// its kill planes, without either its `DEATH_MARGIN` under the lowest platform.
+    /// None for a level with nothing in it
+    pub fn get_death_y(&self) -> Option<f32> {
+        let kill_plane = self.get_entities()
+            .iter()
+            .filter(|e| e.kind == EntityKind::KillPlane)
+            .map(|e| e.pos.y)
+            .chain(self.inner.death_y)
+            .reduce(f32::min);
+
+        kill_plane.or_else(|| {
+            self.get_platforms()
+                .iter()
+                .map(|p| p.pos.y + p.size.y + DEATH_MARGIN)
+                .reduce(f32::max)
+        })
+    }
+
     pub fn draw<'p, 'o>(&'o mut self, renderer: &mut RenderInformation<'p, 'o>) where 'o: 'p {
-        self.inner.draw(&mut self.platform_material, renderer);
+        if let Some(material) = &mut self.platform_material {
+            self.inner.draw(material, renderer);
+        }
     } 
 
-    pub fn get_platform_mat(&mut self) -> &mut Material {
-        &mut self.platform_material
+    pub fn get_platform_mat(&mut self) -> Option<&mut Material> {
+        self.platform_material.as_mut()
+    }
+
+    /// gives a headless level something to draw with
+    pub fn set_platform_mat(&mut self, material: Material) {
+        self.platform_material = Some(material);
     }
 
     pub(crate) fn move_selected_platforms(&mut self, selection: &[usize], delta: Vec2<f32>) {
@@ -47,22 +179,62 @@ impl Level {
         self.inner.add_platform(platform);
     }
 
+    pub fn add_entity(&mut self, entity: Entity) {
+        self.inner.add_entity(entity);
+    }
+
+    pub(crate) fn remove_entity(&mut self, idx: usize) {
+        self.inner.remove_entity(idx);
+    }
+
+    pub(crate) fn set_platform_friction(&mut self, idx: usize, friction: f32) {
+        self.inner.set_platform_friction(idx, friction);
+    }
+
+    /// saves as text if the path ends in `.sglt` and as binary otherwise
     pub fn write_to_file<P: AsRef<Path>>(&self, path: P) -> Result<(), io::Error> {
-        self.inner.write_to_file(path)
+        if is_text_path(path.as_ref()) {
+            std::fs::write(path, self.to_text())
+        } else {
+            self.inner.write_to_file(path)
+        }
+    }
+
+    /// the level as a `.sglt` file
+    pub fn to_text(&self) -> String {
+        self.inner.to_text()
+    }
+
+    /// the level exactly as it would be saved
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.inner.to_bytes()
+    }
+
+    /// identifies the level by what gets played, any edit to that changes it. the metadata
+    /// is left out so renaming a level doesnt throw away its ghosts and personal bests
+    pub fn content_hash(&self) -> u64 {
+        fnv1a(&self.inner.encode(false))
     }
 }
 
 #[derive(Debug, PartialEq)]
 pub(crate) struct InnerLevel {
     platforms: Vec<Platform>,
+    entities: Vec<Entity>,
     player_start: Vec2<f32>,
+    // set by the designer, kill planes can still be higher
+    death_y: Option<f32>,
+    meta: LevelMeta,
 }
 
 impl InnerLevel {
     pub fn new(platforms: Vec<Platform>) -> Self {
         Self {
             platforms,
+            entities: Vec::new(),
             player_start: Vec2 { x: 0.0, y: 0.0},
+            death_y: None,
+            meta: LevelMeta::default(),
         }
     }
 
@@ -70,11 +242,19 @@ impl InnerLevel {
         &self.platforms
     }
 
+    pub fn get_entities(&self) -> &[Entity] {
+        &self.entities
+    }
+
     pub fn draw<'p, 'o>(&'o mut self, platform_material: &'o mut Material, renderer: &mut RenderInformation<'p, 'o>) where 'o: 'p {
         for platform in &self.platforms {
             platform.draw(platform_material, renderer)
         }
 
+        for entity in &self.entities {
+            entity.draw(platform_material, renderer)
+        }
+
         platform_material.draw(renderer);
     }
 
@@ -82,6 +262,22 @@ impl InnerLevel {
         self.platforms.push(platform);
     }
 
+    pub(crate) fn add_entity(&mut self, entity: Entity) {
+        self.entities.push(entity);
+    }
+
+    pub(crate) fn remove_entity(&mut self, idx: usize) {
+        if idx < self.entities.len() {
+            self.entities.remove(idx);
+        }
+    }
+
+    pub(crate) fn set_platform_friction(&mut self, idx: usize, friction: f32) {
+        if let Some(p) = self.platforms.get_mut(idx) {
+            p.friction = friction;
+        }
+    }
+
     pub(crate) fn move_selected_platforms(&mut self, selection: &[usize], delta: Vec2<f32>) {
         self
             .platforms
@@ -91,24 +287,35 @@ impl InnerLevel {
             .for_each(|(_, p)| p.pos += delta);
     }
 
-    pub(crate) fn write_to_file<P: AsRef<Path>>(&self, path: P) -> Result<(), std::io::Error> {
-        let mut platform_bytes = self
-            .platforms
-            .iter()
-            .map(|p| p.to_bytes())
-            .flatten()
-            .collect::<Vec<u8>>();
+    pub(crate) fn to_bytes(&self) -> Vec<u8> {
+        self.encode(true)
+    }
 
+    // levels without metadata dont get a `META` chunk at all
+    fn encode(&self, with_meta: bool) -> Vec<u8> {
         let player_start: [u8; 8] = bytemuck::cast([self.player_start.x.to_le_bytes(), self.player_start.y.to_le_bytes()]);
+        let platforms = record_chunk(self.platforms.iter().map(|p| p.to_bytes()));
+        let entities = record_chunk(self.entities.iter().map(|e| e.to_bytes()));
 
-        let version_numer = 1_u16.to_le_bytes();
+        let mut buffer = Vec::with_capacity(4 + 2 + 8 * 4 + player_start.len() + platforms.len() + entities.len());
+        buffer.extend(FILE_HEADER);
+        buffer.extend(FILE_VERSION.to_le_bytes());
+        write_chunk(&mut buffer, CHUNK_PLAYER_START, &player_start);
+        write_chunk(&mut buffer, CHUNK_PLATFORMS, &platforms);
+        write_chunk(&mut buffer, CHUNK_ENTITIES, &entities);
+        if let Some(death_y) = self.death_y {
+            write_chunk(&mut buffer, CHUNK_DEATH_Y, &death_y.to_le_bytes());
+        }
+        if with_meta && !self.meta.is_empty() {
+            write_chunk(&mut buffer, CHUNK_META, &self.meta.to_bytes());
+        }
+        write_chunk(&mut buffer, CHUNK_END, &[]);
 
-        let mut buffer = Vec::with_capacity(4 + 8 + 2 + platform_bytes.len());
+        buffer
+    }
 
-        buffer.extend(FILE_HEADER);
-        buffer.extend(version_numer);
-        buffer.extend(player_start);
-        buffer.append(&mut platform_bytes);
+    pub(crate) fn write_to_file<P: AsRef<Path>>(&self, path: P) -> Result<(), std::io::Error> {
+        let buffer = self.to_bytes();
 
         let mut file = OpenOptions::new()
             .write(true)
@@ -122,44 +329,312 @@ impl InnerLevel {
         Ok(())
     }
 
-    pub fn read_from_file<P: AsRef<Path>>(path: P) -> Result<Self, std::io::Error> {
-        let file = File::open(path)?;
-        let mut buffer = BufReader::new(file);
+    pub(crate) fn read_from_file<P: AsRef<Path>>(path: P) -> Result<Self, LevelLoadError> {
+        let bytes = std::fs::read(path)?;
+        Self::from_bytes(&bytes)
+    }
 
-        let mut header: [u8; 4] = [0; 4];
-        buffer.read_exact(&mut header)?;
-        assert_eq!(header, FILE_HEADER);
+    /// parses a whole level file, never panics no matter what its given
+    pub(crate) fn from_bytes(bytes: &[u8]) -> Result<Self, LevelLoadError> {
+        let mut reader = ByteReader::new(bytes);
 
-        let mut file_version: [u8; 2] = [0; 2];
-        buffer.read_exact(&mut file_version)?;
-        let file_version = u16::from_le_bytes(file_version);
+        if reader.take(4)? != FILE_HEADER {
+            return Err(LevelLoadError::BadMagic);
+        }
 
-        let mut player_x: [u8; 4] = [0; 4];
-        buffer.read_exact(&mut player_x)?;
-        let mut player_y: [u8; 4] = [0; 4];
-        buffer.read_exact(&mut player_y)?;
-        let player_x = f32::from_le_bytes(player_x);
-        let player_y = f32::from_le_bytes(player_y);
-        let player_start = vec2!(player_x, player_y);
+        // everything from 3 on is chunks, newer versions only add chunks and record
+        // fields that older readers know to skip
+        let file_version = reader.read_u16()?;
+        if file_version >= 3 {
+            return Self::read_chunks(&mut reader);
+        }
 
-        // start with data for one vec!
-        let mut platform_data = Vec::with_capacity(20);
-        buffer.read_to_end(&mut platform_data)?;
+        let player_start = vec2!(reader.read_f32()?, reader.read_f32()?);
 
-        assert_eq!(platform_data.len() % 20, 0);
-        let num_of_platforms = platform_data.len() / 20;
+        let (platforms, entities) = match file_version {
+            1 => (Self::read_v1_platforms(&mut reader)?, Vec::new()),
+            2 => {
+                let platform_count = reader.read_u32()? as usize;
+                let platforms = reader
+                    .take_records::<PLATFORM_RECORD_LEN>(platform_count)?
+                    .enumerate()
+                    .map(|(index, record)| Platform::from_le_bytes(record).ok_or(LevelLoadError::CorruptPlatformRecord { index }))
+                    .collect::<Result<Vec<Platform>, LevelLoadError>>()?;
 
-        let platforms = (0..num_of_platforms)
-            .map(|i| Platform::from_le_bytes(&platform_data[i*20..(i*20)+20]))
-            .collect::<Vec<Platform>>();
+                let entity_count = reader.read_u32()? as usize;
+                let entities = reader
+                    .take_records::<ENTITY_RECORD_LEN>(entity_count)?
+                    .enumerate()
+                    .map(|(index, record)| Entity::from_le_bytes(record).ok_or(LevelLoadError::CorruptEntityRecord { index }))
+                    .collect::<Result<Vec<Entity>, LevelLoadError>>()?;
 
-        drop(buffer);
+                (platforms, entities)
+            },
+            v => return Err(LevelLoadError::UnsupportedVersion(v)),
+        };
 
         Ok(Self {
             platforms,
+            entities,
             player_start,
+            death_y: None,
+            meta: LevelMeta::default(),
         })
     }
+
+    /// version 3 and later files are tagged chunks until the end chunk, ones we dont know are skipped
+    fn read_chunks(reader: &mut ByteReader) -> Result<Self, LevelLoadError> {
+        let mut level = Self::new(Vec::new());
+
+        loop {
+            let tag = reader.take_array::<4>()?;
+            let len = reader.read_u32()? as usize;
+            let mut chunk = ByteReader::new(reader.take(len)?);
+            let corrupt = |_| LevelLoadError::CorruptChunk { tag };
+
+            match tag {
+                CHUNK_END => return Ok(level),
+                CHUNK_PLAYER_START => {
+                    level.player_start = vec2!(chunk.read_f32().map_err(corrupt)?, chunk.read_f32().map_err(corrupt)?);
+                },
+                CHUNK_PLATFORMS => {
+                    for record in chunk.take_chunk_records::<PLATFORM_RECORD_LEN>().map_err(corrupt)? {
+                        let index = level.platforms.len();
+                        let platform = Platform::from_le_bytes(record).ok_or(LevelLoadError::CorruptPlatformRecord { index })?;
+                        level.platforms.push(platform);
+                    }
+                },
+                CHUNK_ENTITIES => {
+                    for record in chunk.take_chunk_records::<ENTITY_RECORD_LEN>().map_err(corrupt)? {
+                        let index = level.entities.len();
+                        let entity = Entity::from_le_bytes(record).ok_or(LevelLoadError::CorruptEntityRecord { index })?;
+                        level.entities.push(entity);
+                    }
+                },
+                CHUNK_DEATH_Y => {
+                    let death_y = chunk.read_f32().map_err(corrupt)?;
+                    if !death_y.is_finite() {
+                        return Err(LevelLoadError::CorruptChunk { tag });
+                    }
+                    level.death_y = Some(death_y);
+                },
+                CHUNK_META => {
+                    level.meta = LevelMeta::from_bytes(&mut chunk).map_err(|e| match e {
+                        LevelLoadError::Truncated => LevelLoadError::CorruptChunk { tag },
+                        e => e,
+                    })?;
+                },
+                // written by something newer, the length lets us step over it
+                _ => {},
+            }
+        }
+    }
+
+    /// version 1 files are just platforms until the end of the file
+    fn read_v1_platforms(reader: &mut ByteReader) -> Result<Vec<Platform>, LevelLoadError> {
+        let rest = reader.rest();
+        if !rest.len().is_multiple_of(PLATFORM_RECORD_LEN) {
+            return Err(LevelLoadError::CorruptPlatformRecord { index: rest.len() / PLATFORM_RECORD_LEN });
+        }
+
+        rest
+            .chunks_exact(PLATFORM_RECORD_LEN)
+            .enumerate()
+            .map(|(index, record)| {
+                let record = record.try_into().expect("chunks are exactly one record long");
+                Platform::from_le_bytes(record).ok_or(LevelLoadError::CorruptPlatformRecord { index })
+            })
+            .collect()
+    }
+}
+
+/// walks through a level file, running off the end is `Truncated` instead of a panic
+struct ByteReader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> ByteReader<'a> {
+    fn new(bytes: &'a [u8]) -> Self {
+        Self {
+            bytes,
+        }
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'a [u8], LevelLoadError> {
+        if self.bytes.len() < len {
+            return Err(LevelLoadError::Truncated);
+        }
+
+        let (taken, rest) = self.bytes.split_at(len);
+        self.bytes = rest;
+        Ok(taken)
+    }
+
+    fn take_array<const N: usize>(&mut self) -> Result<[u8; N], LevelLoadError> {
+        Ok(self.take(N)?.try_into().expect("took exactly N bytes"))
+    }
+
+    /// `count` fixed size records, checked against whats left before anything is allocated
+    fn take_records<const N: usize>(&mut self, count: usize) -> Result<impl Iterator<Item = &'a [u8; N]>, LevelLoadError> {
+        let len = count.checked_mul(N).ok_or(LevelLoadError::Truncated)?;
+        let records = self.take(len)?;
+
+        Ok(records.chunks_exact(N).map(|r| r.try_into().expect("chunks are exactly N bytes")))
+    }
+
+    /// the records in a version 3 chunk, a u16 record length then the records. newer
+    /// versions can make records longer, only the first `N` bytes of each are read
+    fn take_chunk_records<const N: usize>(&mut self) -> Result<impl Iterator<Item = &'a [u8; N]>, LevelLoadError> {
+        let record_len = self.read_u16()? as usize;
+        let records = self.rest();
+        if record_len < N || !records.len().is_multiple_of(record_len) {
+            return Err(LevelLoadError::Truncated);
+        }
+
+        Ok(records.chunks_exact(record_len).map(|r| r[..N].try_into().expect("records are at least N bytes")))
+    }
+
+    fn read_u16(&mut self) -> Result<u16, LevelLoadError> {
+        Ok(u16::from_le_bytes(self.take_array()?))
+    }
+
+    fn read_u32(&mut self) -> Result<u32, LevelLoadError> {
+        Ok(u32::from_le_bytes(self.take_array()?))
+    }
+
+    fn read_f32(&mut self) -> Result<f32, LevelLoadError> {
+        Ok(f32::from_le_bytes(self.take_array()?))
+    }
+
+    fn rest(&mut self) -> &'a [u8] {
+        std::mem::take(&mut self.bytes)
+    }
+}
+
+/// the version a level file was saved as, text files without a version are the current one
+pub fn read_file_version<P: AsRef<Path>>(path: P) -> Result<u16, LevelLoadError> {
+    let path = path.as_ref();
+    if is_text_path(path) {
+        return InnerLevel::text_version(&std::fs::read_to_string(path)?);
+    }
+
+    let bytes = std::fs::read(path)?;
+    let mut reader = ByteReader::new(&bytes);
+    if reader.take(4)? != FILE_HEADER {
+        return Err(LevelLoadError::BadMagic);
+    }
+
+    reader.read_u16()
+}
+
+/// the two ways a level can be written out
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LevelFormat {
+    Binary,
+    Text,
+}
+
+impl LevelFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Binary => BINARY_EXTENSION,
+            Self::Text => TEXT_EXTENSION,
+        }
+    }
+
+    /// what file dialogs call it
+    pub fn description(&self) -> &'static str {
+        match self {
+            Self::Binary => "Speed Game Level Data",
+            Self::Text => "Speed Game Level Text",
+        }
+    }
+
+    /// `path` ending in this formats extension so `Level::write_to_file` writes it this way.
+    /// the other level extension is swapped out, anything else is kept and added to
+    pub fn fix_path(&self, path: &Path) -> PathBuf {
+        match path.extension() {
+            Some(ext) if ext == self.extension() => path.to_path_buf(),
+            Some(ext) if ext == BINARY_EXTENSION || ext == TEXT_EXTENSION => path.with_extension(self.extension()),
+            _ => {
+                let mut fixed = path.as_os_str().to_owned();
+                fixed.push(".");
+                fixed.push(self.extension());
+                PathBuf::from(fixed)
+            },
+        }
+    }
+}
+
+fn is_text_path(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext == TEXT_EXTENSION)
+}
+
+fn write_chunk(buffer: &mut Vec<u8>, tag: [u8; 4], payload: &[u8]) {
+    buffer.extend(tag);
+    buffer.extend((payload.len() as u32).to_le_bytes());
+    buffer.extend(payload);
+}
+
+/// a chunk payload for fixed size records, see `ByteReader::take_chunk_records`
+fn record_chunk<const N: usize>(records: impl Iterator<Item = [u8; N]>) -> Vec<u8> {
+    let mut payload = Vec::from((N as u16).to_le_bytes());
+    payload.extend(records.flatten());
+    payload
+}
+
+/// everything that can go wrong reading a level file
+#[derive(Debug)]
+pub enum LevelLoadError {
+    Io(io::Error),
+    /// doesnt start with `sgld`
+    BadMagic,
+    UnsupportedVersion(u16),
+    /// the file ends before everything it says it has
+    Truncated,
+    /// the platform at `index` is the wrong length or has sizes that make no sense
+    CorruptPlatformRecord {
+        index: usize,
+    },
+    /// the entity at `index` has a kind we dont know or sizes that make no sense
+    CorruptEntityRecord {
+        index: usize,
+    },
+    /// a chunk we know about whose contents dont fit its layout
+    CorruptChunk {
+        tag: [u8; 4],
+    },
+    /// a line in a `.sglt` file that doesnt make sense
+    Text(ConfigError),
+}
+
+impl Display for LevelLoadError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "could not read level: {}", e),
+            Self::BadMagic => write!(f, "not a level file"),
+            Self::UnsupportedVersion(v) => write!(f, "level version {} is not supported", v),
+            Self::Truncated => write!(f, "level file is cut short"),
+            Self::CorruptPlatformRecord { index } => write!(f, "platform {} is corrupt", index),
+            Self::CorruptEntityRecord { index } => write!(f, "entity {} is corrupt", index),
+            Self::CorruptChunk { tag } => write!(f, "{} chunk is corrupt", String::from_utf8_lossy(tag)),
+            Self::Text(e) => write!(f, "bad level text: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for LevelLoadError {}
+
+impl From<io::Error> for LevelLoadError {
+    fn from(value: io::Error) -> Self {
+        Self::Io(value)
+    }
+}
+
+impl From<ConfigError> for LevelLoadError {
+    fn from(value: ConfigError) -> Self {
+        Self::Text(value)
+    }
 }
 
 #[derive(Debug, PartialEq)]
@@ -174,7 +649,15 @@ impl Platform {
         Self {
             pos,
             size,
-            friction: 1.0,
+            friction: NORMAL_FRICTION,
+        }
+    }
+
+    pub fn with_friction(pos: Vec2<f32>, size: Vec2<f32>, friction: f32) -> Self {
+        Self {
+            pos,
+            size,
+            friction,
         }
     }
 
@@ -186,7 +669,7 @@ impl Platform {
         Self {
             size,
             pos,
-            friction: 1.0,
+            friction: NORMAL_FRICTION,
         }
     }
 
@@ -195,10 +678,32 @@ impl Platform {
     }
 
     pub fn draw(&self, mat: &mut Material, renderer: &RenderInformation) {
-        mat.add_rectangle(self.pos, self.size, Colour::WHITE, renderer);
+        mat.add_rectangle(self.pos, self.size, self.colour(), renderer);
+    }
+
+    /// slippery surfaces are tinted blue and grippy ones brown so they can be told apart
+    pub fn colour(&self) -> Colour {
+        if self.friction < NORMAL_FRICTION {
+            Colour::from_rgba(170.0, 220.0, 255.0, 1.0)
+        } else if self.friction > NORMAL_FRICTION {
+            Colour::from_rgba(190.0, 140.0, 90.0, 1.0)
+        } else {
+            Colour::WHITE
+        }
+    }
+
+    /// steps through normal, ice and sticky, used by the editor
+    pub fn next_surface(&self) -> f32 {
+        if self.friction < NORMAL_FRICTION {
+            STICKY_FRICTION
+        } else if self.friction > NORMAL_FRICTION {
+            NORMAL_FRICTION
+        } else {
+            ICE_FRICTION
+        }
     }
 
-    pub(crate) fn to_bytes(&self) -> [u8; 20] {
+    pub(crate) fn to_bytes(&self) -> [u8; PLATFORM_RECORD_LEN] {
         let px = self.pos.x.to_le_bytes();
         let py = self.pos.y.to_le_bytes();
         
@@ -209,24 +714,22 @@ impl Platform {
         bytemuck::cast([px, py, sw, sh, f])
     }
 
-    pub(crate) fn from_le_bytes(bytes: &[u8]) -> Self {
-        let pos = vec2!(
-            f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]),
-            f32::from_le_bytes([bytes[4], bytes[5], bytes[6], bytes[7]])
-        );
-
-        let size = vec2!(
-            f32::from_le_bytes([bytes[8], bytes[9], bytes[10], bytes[11]]),
-            f32::from_le_bytes([bytes[12], bytes[13], bytes[14], bytes[15]])
-        );
+    /// None if the numbers cant be a real platform, like NaNs or a negative size
+    pub(crate) fn from_le_bytes(bytes: &[u8; PLATFORM_RECORD_LEN]) -> Option<Self> {
+        let f = |i: usize| f32::from_le_bytes([bytes[i], bytes[i + 1], bytes[i + 2], bytes[i + 3]]);
+        let platform = Self {
+            pos: vec2!(f(0), f(4)),
+            size: vec2!(f(8), f(12)),
+            friction: f(16),
+        };
 
-        let friction = f32::from_le_bytes([bytes[16], bytes[17], bytes[18], bytes[19]]);
+        platform.is_sensible().then_some(platform)
+    }
 
-        Self {
-            pos,
-            size,
-            friction,
-        }
+    /// no NaNs, infinities, negative sizes or negative friction
+    pub(crate) fn is_sensible(&self) -> bool {
+        let values = [self.pos.x, self.pos.y, self.size.x, self.size.y, self.friction];
+        values.iter().all(|v| v.is_finite()) && self.size.x >= 0.0 && self.size.y >= 0.0 && self.friction >= 0.0
     }
 }
 
@@ -243,12 +746,21 @@ mod tests {
             ],
         );
 
-        level.write_to_file("testingstuff/test_file.sgld").unwrap();
+        level.write_to_file(std::env::temp_dir().join("sgld_test_file.sgld")).unwrap();
     }
 
     #[test]
     fn read_level_from_file() {
-        let l = InnerLevel::read_from_file("testingstuff/test_file.sgld").unwrap();
+        // written here as well so it doesnt depend on the test above running first
+        let path = std::env::temp_dir().join("sgld_read_test_file.sgld");
+        InnerLevel::new(
+            vec![
+                Platform::new(vec2!(10.0, 200.0), vec2!(300.0, 100.0)),
+                Platform::new(vec2!(0.0, 600.0), vec2!(600.0, 50.0)),
+            ],
+        ).write_to_file(&path).unwrap();
+
+        let l = InnerLevel::read_from_file(&path).unwrap();
 
         let orignial_level = InnerLevel::new(
             vec![
@@ -259,4 +771,354 @@ mod tests {
 
         assert_eq!(l, orignial_level);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn friction_round_trips() {
+        let path = std::env::temp_dir().join("sgld_friction_round_trip.sgld");
+        let level = InnerLevel::new(
+            vec![
+                Platform::with_friction(vec2!(0.0), vec2!(100.0, 10.0), ICE_FRICTION),
+                Platform::with_friction(vec2!(0.0, 50.0), vec2!(100.0, 10.0), STICKY_FRICTION),
+                Platform::new(vec2!(0.0, 100.0), vec2!(100.0, 10.0)),
+            ],
+        );
+
+        level.write_to_file(&path).unwrap();
+        let read = InnerLevel::read_from_file(&path).unwrap();
+
+        assert_eq!(read, level);
+        assert_eq!(read.get_platforms()[0].friction, ICE_FRICTION);
+    }
+
+    #[test]
+    fn entities_round_trip() {
+        let path = std::env::temp_dir().join("sgld_entities_round_trip.sgld");
+        let mut level = InnerLevel::new(vec![Platform::new(vec2!(0.0, 100.0), vec2!(100.0, 10.0))]);
+        level.add_entity(Entity::new(EntityKind::Goal, vec2!(50.0, 20.0), vec2!(30.0, 80.0)));
+        level.add_entity(Entity::new(EntityKind::Spikes, vec2!(0.0, 90.0), vec2!(20.0, 10.0)));
+        level.add_entity(Entity::new(EntityKind::KillPlane, vec2!(-500.0, 400.0), vec2!(1000.0, 50.0)));
+        level.add_entity(Entity::new(EntityKind::Checkpoint, vec2!(60.0, 0.0), vec2!(40.0, 100.0)));
+
+        level.write_to_file(&path).unwrap();
+        let read = InnerLevel::read_from_file(&path).unwrap();
+
+        assert_eq!(read, level);
+    }
+
+    #[test]
+    fn load_reports_files_that_arent_levels() {
+        let path = std::env::temp_dir().join("sgld_not_a_level.sgld");
+        std::fs::write(&path, b"hello there, definitely not a level").unwrap();
+
+        assert!(matches!(Level::load(&path), Err(LevelLoadError::BadMagic)));
+        assert!(matches!(Level::load(std::env::temp_dir().join("sgld_missing.sgld")), Err(LevelLoadError::Io(_))));
+    }
+
+    fn full_level() -> InnerLevel {
+        let mut level = InnerLevel::new(vec![
+            Platform::new(vec2!(10.0, 200.0), vec2!(300.0, 100.0)),
+            Platform::with_friction(vec2!(0.0, 600.0), vec2!(600.0, 50.0), ICE_FRICTION),
+        ]);
+        level.player_start = vec2!(40.0, 86.0);
+        level.add_entity(Entity::new(EntityKind::Goal, vec2!(0.0, 480.0), vec2!(80.0, 120.0)));
+        level.add_entity(Entity::new(EntityKind::Spikes, vec2!(100.0, 190.0), vec2!(50.0, 10.0)));
+        level
+    }
+
+    fn full_level_bytes() -> Vec<u8> {
+        full_level().to_bytes()
+    }
+
+    // the flat version 2 layout, nothing writes it anymore
+    fn v2_bytes(level: &InnerLevel) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.extend(FILE_HEADER);
+        bytes.extend(2_u16.to_le_bytes());
+        bytes.extend(level.player_start.x.to_le_bytes());
+        bytes.extend(level.player_start.y.to_le_bytes());
+        bytes.extend((level.platforms.len() as u32).to_le_bytes());
+        bytes.extend(level.platforms.iter().flat_map(|p| p.to_bytes()));
+        bytes.extend((level.entities.len() as u32).to_le_bytes());
+        bytes.extend(level.entities.iter().flat_map(|e| e.to_bytes()));
+        bytes
+    }
+
+    #[test]
+    fn bounds_cover_everything_and_the_player() {
+        let level = Level {
+            platform_material: None,
+            inner: full_level(),
+        };
+
+        let (min, max) = level.get_bounds();
+        assert_eq!((min.x, min.y), (0.0, 86.0));
+        assert_eq!((max.x, max.y), (600.0, 650.0));
+
+        // an empty level is just the player
+        let (min, max) = Level::headless(vec![]).get_bounds();
+        assert_eq!((max.x - min.x, max.y - min.y), (PLAYER_SIZE.x, PLAYER_SIZE.y));
+    }
+
+    #[test]
+    fn file_versions_are_read_from_either_format() {
+        let dir = std::env::temp_dir();
+        let binary = dir.join("sgld_version_test.sgld");
+        let text = dir.join("sgld_version_test.sglt");
+        std::fs::write(&binary, v2_bytes(&full_level())).unwrap();
+        std::fs::write(&text, "platform = 0, 0, 1, 1, 1\n").unwrap();
+
+        assert_eq!(read_file_version(&binary).unwrap(), 2);
+        assert_eq!(read_file_version(&text).unwrap(), FILE_VERSION);
+    }
+
+    #[test]
+    fn metadata_round_trips_without_changing_the_hash() {
+        let mut level = Level {
+            platform_material: None,
+            inner: full_level(),
+        };
+        let hash = level.content_hash();
+
+        let meta = level.get_mut_meta();
+        meta.name = String::from("first steps");
+        meta.author = String::from("shork");
+        meta.difficulty = Some(Difficulty::Easy);
+        meta.set_medal_time(Medal::Gold, Some(4.5));
+
+        let read = InnerLevel::from_bytes(&level.to_bytes()).unwrap();
+        assert_eq!(read, level.inner);
+        assert_eq!(level.content_hash(), hash);
+        // and a level without any leaves the chunk out
+        assert_eq!(full_level_bytes().windows(4).filter(|w| *w == CHUNK_META).count(), 0);
+    }
+
+    #[test]
+    fn version_two_files_still_load_and_save_as_chunks() {
+        let level = full_level();
+        let read = InnerLevel::from_bytes(&v2_bytes(&level)).unwrap();
+        assert_eq!(read, level);
+
+        let saved = read.to_bytes();
+        assert_eq!(saved[4..6], FILE_VERSION.to_le_bytes());
+        assert_eq!(InnerLevel::from_bytes(&saved).unwrap(), level);
+    }
+
+    #[test]
+    fn version_two_counts_cant_ask_for_more_than_the_file_has() {
+        let level = full_level();
+        let platforms_at = 4 + 2 + 8;
+        let entities_at = platforms_at + 4 + level.platforms.len() * PLATFORM_RECORD_LEN;
+
+        for at in [platforms_at, entities_at] {
+            for count in [u32::MAX, u32::MAX / PLATFORM_RECORD_LEN as u32 + 1, 1_000_000] {
+                let mut bytes = v2_bytes(&level);
+                bytes[at..at + 4].copy_from_slice(&count.to_le_bytes());
+                assert!(matches!(InnerLevel::from_bytes(&bytes), Err(LevelLoadError::Truncated)), "count {} at {}", count, at);
+            }
+        }
+    }
+
+    #[test]
+    fn unknown_chunks_and_longer_records_are_skipped() {
+        let platform = Platform::new(vec2!(1.0, 2.0), vec2!(3.0, 4.0));
+
+        // what a newer version might write, an extra chunk and platforms with a field on the end
+        let mut platforms = Vec::from(((PLATFORM_RECORD_LEN + 4) as u16).to_le_bytes());
+        platforms.extend(platform.to_bytes());
+        platforms.extend(7.5_f32.to_le_bytes());
+
+        let mut bytes = Vec::new();
+        bytes.extend(FILE_HEADER);
+        bytes.extend(FILE_VERSION.to_le_bytes());
+        write_chunk(&mut bytes, *b"ZZZZ", b"from the future");
+        write_chunk(&mut bytes, CHUNK_PLATFORMS, &platforms);
+        write_chunk(&mut bytes, CHUNK_END, &[]);
+
+        let read = InnerLevel::from_bytes(&bytes).unwrap();
+        assert_eq!(read.get_platforms(), &[platform]);
+        assert_eq!(read.player_start, vec2!(0.0, 0.0));
+    }
+
+    // xorshift, good enough to throw junk at the reader without pulling in a crate
+    fn next_random(state: &mut u64) -> u64 {
+        *state ^= *state << 13;
+        *state ^= *state >> 7;
+        *state ^= *state << 17;
+        *state
+    }
+
+    #[test]
+    fn format_picks_the_extension() {
+        assert_eq!(LevelFormat::Text.fix_path(Path::new("out.sgld")), Path::new("out.sglt"));
+        assert_eq!(LevelFormat::Text.fix_path(Path::new("out.sglt")), Path::new("out.sglt"));
+        assert_eq!(LevelFormat::Binary.fix_path(Path::new("levels/out.sglt")), Path::new("levels/out.sgld"));
+        assert_eq!(LevelFormat::Text.fix_path(Path::new("out")), Path::new("out.sglt"));
+        assert_eq!(LevelFormat::Binary.fix_path(Path::new("jump.v2")), Path::new("jump.v2.sgld"));
+
+        // and the fixed path really is written that way
+        let level = Level {
+            platform_material: None,
+            inner: full_level(),
+        };
+        let path = LevelFormat::Text.fix_path(&std::env::temp_dir().join("sgld_format_choice.sgld"));
+        level.write_to_file(&path).unwrap();
+        assert!(std::fs::read_to_string(&path).unwrap().contains("player_start = 40, 86"));
+        assert_eq!(Level::load(&path).unwrap().inner, level.inner);
+    }
+
+    #[test]
+    fn newer_versions_load_what_they_can() {
+        let level = full_level();
+        let mut bytes = Vec::new();
+        bytes.extend(FILE_HEADER);
+        bytes.extend(4_u16.to_le_bytes());
+        write_chunk(&mut bytes, CHUNK_PLAYER_START, &bytemuck::cast::<_, [u8; 8]>([level.player_start.x.to_le_bytes(), level.player_start.y.to_le_bytes()]));
+        write_chunk(&mut bytes, *b"WIND", &[1, 2, 3, 4, 5]);
+        write_chunk(&mut bytes, CHUNK_PLATFORMS, &record_chunk(level.platforms.iter().map(|p| p.to_bytes())));
+        write_chunk(&mut bytes, CHUNK_ENTITIES, &record_chunk(level.entities.iter().map(|e| e.to_bytes())));
+        write_chunk(&mut bytes, CHUNK_END, &[]);
+
+        assert_eq!(InnerLevel::from_bytes(&bytes).unwrap(), level);
+    }
+
+    #[test]
+    fn typed_errors_for_bad_files() {
+        let bytes = v2_bytes(&full_level());
+
+        let mut bad_magic = bytes.clone();
+        bad_magic[0] = b'x';
+        assert!(matches!(InnerLevel::from_bytes(&bad_magic), Err(LevelLoadError::BadMagic)));
+
+        // there was never a version 0, anything past 3 is read as chunks
+        let mut zero = bytes.clone();
+        zero[4..6].copy_from_slice(&0_u16.to_le_bytes());
+        assert!(matches!(InnerLevel::from_bytes(&zero), Err(LevelLoadError::UnsupportedVersion(0))));
+
+        // second platforms width
+        let mut nan = bytes.clone();
+        let width = 4 + 2 + 8 + 4 + PLATFORM_RECORD_LEN + 8;
+        nan[width..width + 4].copy_from_slice(&f32::NAN.to_le_bytes());
+        assert!(matches!(InnerLevel::from_bytes(&nan), Err(LevelLoadError::CorruptPlatformRecord { index: 1 })));
+
+        // claims far more platforms than the file could hold, shouldnt try to allocate them
+        let mut huge = bytes.clone();
+        huge[14..18].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert!(matches!(InnerLevel::from_bytes(&huge), Err(LevelLoadError::Truncated)));
+
+        let mut v1 = bytes[..14].to_vec();
+        v1[4..6].copy_from_slice(&1_u16.to_le_bytes());
+        v1.extend([0; PLATFORM_RECORD_LEN + 3]);
+        assert!(matches!(InnerLevel::from_bytes(&v1), Err(LevelLoadError::CorruptPlatformRecord { index: 1 })));
+
+        // a version 3 chunk claiming to be bigger than the file
+        let mut chunks = full_level_bytes();
+        chunks[10..14].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert!(matches!(InnerLevel::from_bytes(&chunks), Err(LevelLoadError::Truncated)));
+
+        let mut short_start = Vec::new();
+        short_start.extend(FILE_HEADER);
+        short_start.extend(FILE_VERSION.to_le_bytes());
+        write_chunk(&mut short_start, CHUNK_PLAYER_START, &[0; 4]);
+        write_chunk(&mut short_start, CHUNK_END, &[]);
+        assert!(matches!(InnerLevel::from_bytes(&short_start), Err(LevelLoadError::CorruptChunk { tag: CHUNK_PLAYER_START })));
+    }
+
+    #[test]
+    fn every_truncation_is_an_error() {
+        for bytes in [full_level_bytes(), v2_bytes(&full_level())] {
+            assert!(InnerLevel::from_bytes(&bytes).is_ok());
+
+            for len in 0..bytes.len() {
+                assert!(matches!(InnerLevel::from_bytes(&bytes[..len]), Err(LevelLoadError::Truncated)), "cut at {}", len);
+            }
+        }
+    }
+
+    #[test]
+    fn corrupted_files_never_panic() {
+        let mut state = 0x5eed_1e7e1_u64;
+
+        // every byte set to a handful of values that tend to break things
+        for bytes in [full_level_bytes(), v2_bytes(&full_level())] {
+            for i in 0..bytes.len() {
+                for value in [0, 1, 0x7f, 0x80, 0xff] {
+                    let mut corrupt = bytes.clone();
+                    corrupt[i] = value;
+                    let _ = InnerLevel::from_bytes(&corrupt);
+                }
+            }
+        }
+
+        // random bytes after a valid header so the parser gets past the first checks
+        for _ in 0..5000 {
+            let len = (next_random(&mut state) % 200) as usize;
+            let mut junk = Vec::with_capacity(len + 6);
+            junk.extend(FILE_HEADER);
+            junk.extend(((next_random(&mut state) % 4) as u16).to_le_bytes());
+            junk.extend((0..len).map(|_| next_random(&mut state) as u8));
+
+            let _ = InnerLevel::from_bytes(&junk);
+            let _ = InnerLevel::from_bytes(&junk[4..]);
+        }
+    }
+
+    #[test]
+    fn death_y_comes_from_the_highest_kill_plane() {
+        let mut level = Level::headless(vec![
+            Platform::new(vec2!(0.0, 100.0), vec2!(100.0, 10.0)),
+            Platform::new(vec2!(0.0, 300.0), vec2!(100.0, 20.0)),
+        ]);
+        assert_eq!(level.get_death_y(), Some(320.0 + DEATH_MARGIN));
+
+        level.add_entity(Entity::new(EntityKind::KillPlane, vec2!(0.0, 500.0), vec2!(100.0, 10.0)));
+        level.add_entity(Entity::new(EntityKind::KillPlane, vec2!(0.0, 400.0), vec2!(100.0, 10.0)));
+        assert_eq!(level.get_death_y(), Some(400.0));
+
+        assert_eq!(Level::headless(vec![]).get_death_y(), None);
+
+        // a stored death y only wins if its above every kill plane
+        level.set_death_y(Some(450.0));
+        assert_eq!(level.get_death_y(), Some(400.0));
+        level.set_death_y(Some(250.0));
+        assert_eq!(level.get_death_y(), Some(250.0));
+    }
+
+    #[test]
+    fn stored_death_y_round_trips_and_changes_the_hash() {
+        let mut level = Level {
+            platform_material: None,
+            inner: full_level(),
+        };
+        let without = level.content_hash();
+        level.set_death_y(Some(900.0));
+        assert_ne!(level.content_hash(), without);
+
+        let from_bytes = InnerLevel::from_bytes(&level.inner.to_bytes()).unwrap();
+        assert_eq!(from_bytes.death_y, Some(900.0));
+        assert_eq!(InnerLevel::from_text(&level.to_text()).unwrap(), from_bytes);
+
+        let mut bytes = Vec::new();
+        bytes.extend(FILE_HEADER);
+        bytes.extend(FILE_VERSION.to_le_bytes());
+        write_chunk(&mut bytes, CHUNK_DEATH_Y, &f32::NAN.to_le_bytes());
+        write_chunk(&mut bytes, CHUNK_END, &[]);
+        assert!(matches!(InnerLevel::from_bytes(&bytes), Err(LevelLoadError::CorruptChunk { tag: CHUNK_DEATH_Y })));
+    }
+
+    #[test]
+    fn version_one_files_still_load() {
+        let path = std::env::temp_dir().join("sgld_version_one.sgld");
+        let platform = Platform::new(vec2!(5.0, 6.0), vec2!(7.0, 8.0));
+
+        let mut bytes = Vec::new();
+        bytes.extend(FILE_HEADER);
+        bytes.extend(1_u16.to_le_bytes());
+        bytes.extend([0; 8]);
+        bytes.extend(platform.to_bytes());
+        std::fs::write(&path, bytes).unwrap();
+
+        let read = InnerLevel::read_from_file(&path).unwrap();
+        assert_eq!(read.get_platforms(), &[platform]);
+        assert!(read.get_entities().is_empty());
+    }
+}
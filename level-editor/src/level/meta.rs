@@ -0,0 +1,201 @@
+//! who made a level and how fast it should be beaten. none of it changes how the level plays
+
+use super::{ByteReader, LevelLoadError};
+
+/// how hard the level is meant to be, shown before playing
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Difficulty {
+    Easy,
+    Normal,
+    Hard,
+    Expert,
+}
+
+impl Difficulty {
+    pub const ALL: [Self; 4] = [Self::Easy, Self::Normal, Self::Hard, Self::Expert];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Easy => "easy",
+            Self::Normal => "normal",
+            Self::Hard => "hard",
+            Self::Expert => "expert",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|d| d.name() == name)
+    }
+
+    /// one tier up, None after the hardest so the editor can cycle back to unrated
+    pub fn harder(difficulty: Option<Self>) -> Option<Self> {
+        match difficulty {
+            None => Some(Self::Easy),
+            Some(Self::Easy) => Some(Self::Normal),
+            Some(Self::Normal) => Some(Self::Hard),
+            Some(Self::Hard) => Some(Self::Expert),
+            Some(Self::Expert) => None,
+        }
+    }
+
+    // 0 is unrated in files
+    fn to_byte(difficulty: Option<Self>) -> u8 {
+        match difficulty {
+            None => 0,
+            Some(Self::Easy) => 1,
+            Some(Self::Normal) => 2,
+            Some(Self::Hard) => 3,
+            Some(Self::Expert) => 4,
+        }
+    }
+
+    fn from_byte(byte: u8) -> Result<Option<Self>, LevelLoadError> {
+        match byte {
+            0 => Ok(None),
+            1..=4 => Ok(Some(Self::ALL[byte as usize - 1])),
+            _ => Err(LevelLoadError::CorruptChunk { tag: super::CHUNK_META }),
+        }
+    }
+}
+
+/// best first
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Medal {
+    Gold,
+    Silver,
+    Bronze,
+}
+
+impl Medal {
+    pub const ALL: [Self; 3] = [Self::Gold, Self::Silver, Self::Bronze];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Gold => "gold",
+            Self::Silver => "silver",
+            Self::Bronze => "bronze",
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct LevelMeta {
+    pub name: String,
+    pub author: String,
+    pub description: String,
+    /// None if nobody has rated it
+    pub difficulty: Option<Difficulty>,
+    // seconds to beat for each medal in `Medal::ALL` order
+    medal_times: [Option<f32>; 3],
+}
+
+impl LevelMeta {
+    /// seconds a run has to beat for `medal`, None if the level doesnt give it out
+    pub fn get_medal_time(&self, medal: Medal) -> Option<f32> {
+        self.medal_times[medal as usize]
+    }
+
+    /// times that arent a positive number of seconds unset the medal
+    pub fn set_medal_time(&mut self, medal: Medal, secs: Option<f32>) {
+        self.medal_times[medal as usize] = secs.filter(|s| s.is_finite() && *s > 0.0);
+    }
+
+    /// the time shown before playing, the gold time
+    pub fn get_par_time(&self) -> Option<f32> {
+        self.get_medal_time(Medal::Gold)
+    }
+
+    /// the best medal a run of `secs` earns
+    pub fn medal_for(&self, secs: f32) -> Option<Medal> {
+        Medal::ALL
+            .into_iter()
+            .find(|m| self.get_medal_time(*m).is_some_and(|t| secs <= t))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// the `META` chunk, see readme.md
+    pub(super) fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = vec![Difficulty::to_byte(self.difficulty)];
+        for time in self.medal_times {
+            bytes.extend(time.unwrap_or(0.0).to_le_bytes());
+        }
+        for text in [&self.name, &self.author, &self.description] {
+            write_string(&mut bytes, text);
+        }
+
+        bytes
+    }
+
+    /// anything after the fields we know is from a newer version and gets left alone
+    pub(super) fn from_bytes(reader: &mut ByteReader) -> Result<Self, LevelLoadError> {
+        let mut meta = Self {
+            difficulty: Difficulty::from_byte(reader.take_array::<1>()?[0])?,
+            ..Self::default()
+        };
+
+        for medal in Medal::ALL {
+            let secs = reader.read_f32()?;
+            if !secs.is_finite() || secs < 0.0 {
+                return Err(LevelLoadError::CorruptChunk { tag: super::CHUNK_META });
+            }
+            meta.set_medal_time(medal, Some(secs));
+        }
+
+        for text in [&mut meta.name, &mut meta.author, &mut meta.description] {
+            let len = reader.read_u16()? as usize;
+            *text = std::str::from_utf8(reader.take(len)?)
+                .map_err(|_| LevelLoadError::CorruptChunk { tag: super::CHUNK_META })?
+                .to_string();
+        }
+
+        Ok(meta)
+    }
+}
+
+// u16 length then utf8, anything past u16::MAX bytes is cut off at a char boundary
+fn write_string(bytes: &mut Vec<u8>, text: &str) {
+    let mut end = text.len().min(u16::MAX as usize);
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+
+    bytes.extend((end as u16).to_le_bytes());
+    bytes.extend(&text.as_bytes()[..end]);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn medals_go_to_the_best_time_beaten() {
+        let mut meta = LevelMeta::default();
+        meta.set_medal_time(Medal::Gold, Some(10.0));
+        meta.set_medal_time(Medal::Bronze, Some(20.0));
+
+        assert_eq!(meta.medal_for(9.5), Some(Medal::Gold));
+        assert_eq!(meta.medal_for(15.0), Some(Medal::Bronze));
+        assert_eq!(meta.medal_for(20.5), None);
+        assert_eq!(meta.get_par_time(), Some(10.0));
+
+        meta.set_medal_time(Medal::Gold, Some(-1.0));
+        assert_eq!(meta.get_medal_time(Medal::Gold), None);
+    }
+
+    #[test]
+    fn long_strings_are_cut_at_a_char_boundary() {
+        let meta = LevelMeta {
+            // 2 byte chars so u16::MAX lands in the middle of one
+            description: "\u{e9}".repeat(40_000),
+            ..LevelMeta::default()
+        };
+
+        let bytes = meta.to_bytes();
+        let read = LevelMeta::from_bytes(&mut ByteReader::new(&bytes)).unwrap();
+        assert_eq!(read.description.len(), u16::MAX as usize - 1);
+        assert!(meta.description.starts_with(&read.description));
+    }
+}
@@ -0,0 +1,268 @@
+//! `.sglt`, the same data as a `.sgld` but as `key = value` lines so level changes can be
+//! read in a diff. numbers are written so they read back exactly
+
+use std::fmt::Write;
+
+use bottomless_pit::vec2;
+use utils::config::{parse_entries_with, ConfigEntry, ConfigError};
+
+use super::{Difficulty, InnerLevel, LevelLoadError, Medal, Platform, FILE_VERSION};
+use crate::entity::{Entity, EntityKind};
+
+impl InnerLevel {
+    pub(crate) fn to_text(&self) -> String {
+        let mut text = String::new();
+        // writing to a String cant fail
+        let _ = self.write_text(&mut text);
+        text
+    }
+
+    fn write_text(&self, text: &mut String) -> std::fmt::Result {
+        writeln!(text, "# speed game level, the text version of a .sgld file")?;
+        writeln!(text, "version = {}", FILE_VERSION)?;
+        writeln!(text, "player_start = {}, {}", self.player_start.x, self.player_start.y)?;
+        if let Some(death_y) = self.death_y {
+            writeln!(text, "death_y = {}", death_y)?;
+        }
+
+        if !self.meta.is_empty() {
+            writeln!(text)?;
+            writeln!(text, "# about the level, only what is set gets written")?;
+            for (key, value) in [("name", &self.meta.name), ("author", &self.meta.author), ("description", &self.meta.description)] {
+                if !value.is_empty() {
+                    writeln!(text, "{} = {}", key, escape(value))?;
+                }
+            }
+            if let Some(difficulty) = self.meta.difficulty {
+                writeln!(text, "difficulty = {}", difficulty.name())?;
+            }
+            for medal in Medal::ALL {
+                if let Some(secs) = self.meta.get_medal_time(medal) {
+                    writeln!(text, "{} = {}", medal.name(), secs)?;
+                }
+            }
+        }
+
+        writeln!(text)?;
+        writeln!(text, "# x, y, width, height, friction")?;
+        for p in &self.platforms {
+            writeln!(text, "platform = {}, {}, {}, {}, {}", p.pos.x, p.pos.y, p.size.x, p.size.y, p.friction)?;
+        }
+
+        writeln!(text)?;
+        writeln!(text, "# kind, x, y, width, height")?;
+        for e in &self.entities {
+            writeln!(text, "entity = {}, {}, {}, {}, {}", e.kind.name(), e.pos.x, e.pos.y, e.size.x, e.size.y)?;
+        }
+
+        Ok(())
+    }
+
+    pub(crate) fn text_version(text: &str) -> Result<u16, LevelLoadError> {
+        let Some(entry) = parse_entries_with(text, strip_comment)?.into_iter().find(|e| e.key == "version") else {
+            return Ok(FILE_VERSION);
+        };
+
+        let version = entry.parse_u32()?;
+        u16::try_from(version).map_err(|_| LevelLoadError::UnsupportedVersion(u16::MAX))
+    }
+
+    pub(crate) fn from_text(text: &str) -> Result<Self, LevelLoadError> {
+        let mut level = Self::new(Vec::new());
+
+        for entry in parse_entries_with(text, strip_comment)? {
+            match entry.key {
+                "version" => {
+                    let version = entry.parse_u32()?;
+                    if version > FILE_VERSION as u32 {
+                        return Err(LevelLoadError::UnsupportedVersion(u16::try_from(version).unwrap_or(u16::MAX)));
+                    }
+                },
+                "name" => level.meta.name = unescape(entry.value),
+                "author" => level.meta.author = unescape(entry.value),
+                "description" => level.meta.description = unescape(entry.value),
+                "difficulty" => {
+                    level.meta.difficulty = Some(Difficulty::from_name(entry.value)
+                        .ok_or_else(|| ConfigError::new(entry.line, format!("unknown difficulty `{}`", entry.value)))?);
+                },
+                "gold" | "silver" | "bronze" => {
+                    let medal = Medal::ALL.into_iter().find(|m| m.name() == entry.key).expect("matched a medal name");
+                    let secs = entry.parse_f32()?;
+                    if !secs.is_finite() || secs <= 0.0 {
+                        return Err(ConfigError::new(entry.line, format!("`{}` expects a time in seconds", entry.key)).into());
+                    }
+                    level.meta.set_medal_time(medal, Some(secs));
+                },
+                "death_y" => {
+                    let death_y = entry.parse_f32()?;
+                    if !death_y.is_finite() {
+                        return Err(ConfigError::new(entry.line, "`death_y` expects a number").into());
+                    }
+                    level.death_y = Some(death_y);
+                },
+                "player_start" => {
+                    let [x, y] = parse_numbers(&entry)?;
+                    level.player_start = vec2!(x, y);
+                },
+                "platform" => {
+                    let [x, y, w, h, friction] = parse_numbers(&entry)?;
+                    let platform = Platform::with_friction(vec2!(x, y), vec2!(w, h), friction);
+                    if !platform.is_sensible() {
+                        return Err(LevelLoadError::CorruptPlatformRecord { index: level.platforms.len() });
+                    }
+                    level.platforms.push(platform);
+                },
+                "entity" => {
+                    let Some((kind, rest)) = entry.value.split_once(',') else {
+                        return Err(ConfigError::new(entry.line, "`entity` expects a kind then four numbers").into());
+                    };
+                    let kind = EntityKind::from_name(kind.trim())
+                        .ok_or_else(|| ConfigError::new(entry.line, format!("unknown entity kind `{}`", kind.trim())))?;
+                    let [x, y, w, h] = parse_numbers(&ConfigEntry { value: rest, ..entry.clone() })?;
+
+                    let entity = Entity::new(kind, vec2!(x, y), vec2!(w, h));
+                    if !entity.is_sensible() {
+                        return Err(LevelLoadError::CorruptEntityRecord { index: level.entities.len() });
+                    }
+                    level.entities.push(entity);
+                },
+                _ => return Err(entry.unknown_key().into()),
+            }
+        }
+
+        Ok(level)
+    }
+}
+
+/// text values cant hold a `#` or a new line and lose spaces at either end, so those are
+/// written with a `\` in front
+fn escape(value: &str) -> String {
+    let last = value.chars().count().saturating_sub(1);
+    let mut escaped = String::with_capacity(value.len());
+
+    for (idx, c) in value.chars().enumerate() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '#' => escaped.push_str("\\#"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            ' ' if idx == 0 || idx == last => escaped.push_str("\\s"),
+            c => escaped.push(c),
+        }
+    }
+
+    escaped
+}
+
+// like the settings files a `#` starts a comment, but not a `\#` written by `escape`
+fn strip_comment(line: &str) -> &str {
+    let mut escaped = false;
+    for (idx, c) in line.char_indices() {
+        match c {
+            '\\' if !escaped => escaped = true,
+            '#' if !escaped => return &line[..idx],
+            _ => escaped = false,
+        }
+    }
+
+    line
+}
+
+// a `\` before anything else is kept as is
+fn unescape(value: &str) -> String {
+    let mut unescaped = String::with_capacity(value.len());
+    let mut chars = value.chars();
+
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+
+        match chars.next() {
+            Some('n') => unescaped.push('\n'),
+            Some('r') => unescaped.push('\r'),
+            Some('t') => unescaped.push('\t'),
+            Some('s') => unescaped.push(' '),
+            Some(c @ ('\\' | '#')) => unescaped.push(c),
+            Some(c) => {
+                unescaped.push('\\');
+                unescaped.push(c);
+            },
+            None => unescaped.push('\\'),
+        }
+    }
+
+    unescaped
+}
+
+/// exactly `N` comma separated numbers
+fn parse_numbers<const N: usize>(entry: &ConfigEntry) -> Result<[f32; N], ConfigError> {
+    let wrong = || ConfigError::new(entry.line, format!("`{}` expects {} numbers separated by commas", entry.key, N));
+
+    let mut numbers = [0.0; N];
+    let mut parts = entry.value.split(',');
+    for number in &mut numbers {
+        *number = parts
+            .next()
+            .and_then(|p| p.trim().parse::<f32>().ok())
+            .ok_or_else(wrong)?;
+    }
+
+    if parts.next().is_some() {
+        return Err(wrong());
+    }
+
+    Ok(numbers)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::level::ICE_FRICTION;
+
+    #[test]
+    fn round_trips_exactly() {
+        let mut level = InnerLevel::new(vec![
+            Platform::new(vec2!(10.0, 200.0), vec2!(300.0, 100.0)),
+            Platform::with_friction(vec2!(-0.1, 1.0 / 3.0), vec2!(600.5, 50.0), ICE_FRICTION),
+        ]);
+        level.player_start = vec2!(40.0, 86.1234);
+        level.add_entity(Entity::new(EntityKind::KillPlane, vec2!(-500.0, 1e7), vec2!(1000.0, 50.0)));
+        level.add_entity(Entity::new(EntityKind::Checkpoint, vec2!(0.1, 0.2), vec2!(0.3, 0.4)));
+        level.meta.name = String::from(" level #1\\n ");
+        level.meta.author = String::from("shork");
+        level.meta.description = String::from("two lines\nand a\ttab");
+        level.meta.difficulty = Some(Difficulty::Expert);
+        level.meta.set_medal_time(Medal::Silver, Some(12.345_678));
+
+        let text = level.to_text();
+        let read = InnerLevel::from_text(&text).unwrap();
+        assert_eq!(read, level);
+        // and the binary version agrees byte for byte
+        assert_eq!(read.to_bytes(), level.to_bytes());
+    }
+
+    #[test]
+    fn escaped_hashes_arent_comments() {
+        let level = InnerLevel::from_text("name = level \\#1 # the first one\nauthor = a\\\\# comment").unwrap();
+
+        assert_eq!(level.meta.name, "level #1");
+        assert_eq!(level.meta.author, "a\\");
+    }
+
+    #[test]
+    fn reports_the_bad_line() {
+        let text = "version = 3\nplatform = 1, 2, 3\n";
+        assert!(matches!(InnerLevel::from_text(text), Err(LevelLoadError::Text(e)) if e.line == 2));
+
+        let text = "entity = lava, 1, 2, 3, 4\n";
+        assert!(matches!(InnerLevel::from_text(text), Err(LevelLoadError::Text(e)) if e.line == 1));
+
+        let text = "platform = 0, 0, -5, 10, 1\n";
+        assert!(matches!(InnerLevel::from_text(text), Err(LevelLoadError::CorruptPlatformRecord { index: 0 })));
+
+        assert!(matches!(InnerLevel::from_text("version = 99"), Err(LevelLoadError::UnsupportedVersion(99))));
+    }
+}
@@ -0,0 +1,210 @@
+//! finds things in a level that are broken or pointless before anyone tries to play it
+
+use std::fmt::Display;
+
+use utils::collision;
+
+use super::{Level, Medal, PLAYER_SIZE};
+use crate::entity::EntityKind;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    /// the level works but probably not how it was meant to
+    Warning,
+    /// the level cant be played like this
+    Error,
+}
+
+/// which part of the level a diagnostic is about
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Subject {
+    Level,
+    PlayerStart,
+    Platform(usize),
+    Entity(usize),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub subject: Subject,
+    pub message: String,
+}
+
+impl Diagnostic {
+    fn new(severity: Severity, subject: Subject, message: impl Into<String>) -> Self {
+        Self {
+            severity,
+            subject,
+            message: message.into(),
+        }
+    }
+
+    pub fn is_error(&self) -> bool {
+        self.severity == Severity::Error
+    }
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let severity = match self.severity {
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        };
+
+        match self.subject {
+            Subject::Level => write!(f, "{}: level {}", severity, self.message),
+            Subject::PlayerStart => write!(f, "{}: player start {}", severity, self.message),
+            Subject::Platform(idx) => write!(f, "{}: platform {} {}", severity, idx, self.message),
+            Subject::Entity(idx) => write!(f, "{}: entity {} {}", severity, idx, self.message),
+        }
+    }
+}
+
+impl Level {
+    /// everything wrong with the level in the order its found, empty if its fine
+    pub fn validate(&self) -> Vec<Diagnostic> {
+        let mut diagnostics = Vec::new();
+        let mut push = |severity, subject, message: &str| diagnostics.push(Diagnostic::new(severity, subject, message));
+
+        let start = self.get_player_start();
+        if !start.x.is_finite() || !start.y.is_finite() {
+            push(Severity::Error, Subject::PlayerStart, "is not a real position");
+        }
+
+        if self.get_stored_death_y().is_some_and(|y| !y.is_finite()) {
+            push(Severity::Error, Subject::Level, "has a death line that is not a real number");
+        } else if self.get_death_y().is_some_and(|y| start.y + PLAYER_SIZE.y > y) {
+            push(Severity::Error, Subject::PlayerStart, "is bellow the death line so the player dies straight away");
+        }
+
+        for (idx, platform) in self.get_platforms().iter().enumerate() {
+            let subject = Subject::Platform(idx);
+            let values = [platform.pos.x, platform.pos.y, platform.size.x, platform.size.y];
+
+            if values.iter().any(|v| !v.is_finite()) {
+                push(Severity::Error, subject, "has a position or size that is not a real number");
+            } else if platform.size.x < 0.0 || platform.size.y < 0.0 {
+                push(Severity::Error, subject, "has a negative size");
+            } else if platform.size.x == 0.0 || platform.size.y == 0.0 {
+                push(Severity::Warning, subject, "has no area so nothing can touch it");
+            } else if collision::rect_overlaps(start, PLAYER_SIZE, platform.pos, platform.size) {
+                push(Severity::Error, subject, "has the player start inside it");
+            }
+
+            if !platform.friction.is_finite() || platform.friction < 0.0 {
+                push(Severity::Error, subject, "has friction that is negative or not a real number");
+            } else if platform.friction == 0.0 {
+                push(Severity::Warning, subject, "has no friction so the player cant speed up or stop on it");
+            }
+        }
+
+        for (idx, entity) in self.get_entities().iter().enumerate() {
+            let subject = Subject::Entity(idx);
+
+            if !entity.is_sensible() {
+                push(Severity::Error, subject, "has a position or size that is negative or not a real number");
+            } else if entity.size.x == 0.0 || entity.size.y == 0.0 {
+                push(Severity::Warning, subject, "has no area so it can never be touched");
+            }
+        }
+
+        if !self.get_entities().iter().any(|e| e.kind == EntityKind::Goal) {
+            push(Severity::Warning, Subject::Level, "has no goal so it can never be finished");
+        }
+
+        // unset medals are skipped, the ones that are set have to get easier going down
+        let medal_times = Medal::ALL
+            .iter()
+            .filter_map(|m| self.get_meta().get_medal_time(*m))
+            .collect::<Vec<f32>>();
+        if medal_times.windows(2).any(|pair| pair[0] > pair[1]) {
+            push(Severity::Warning, Subject::Level, "has medal times out of order, gold should be fastest then silver then bronze");
+        }
+
+        diagnostics
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bottomless_pit::vec2;
+
+    use super::*;
+    use crate::entity::Entity;
+    use crate::level::Platform;
+
+    fn playable() -> Level {
+        let mut level = Level::headless(vec![Platform::new(vec2!(0.0, 600.0), vec2!(600.0, 50.0))]);
+        level.set_player_start(vec2!(0.0, 600.0 - PLAYER_SIZE.y));
+        level.add_entity(Entity::new(EntityKind::Goal, vec2!(500.0, 480.0), vec2!(80.0, 120.0)));
+        level
+    }
+
+    #[test]
+    fn a_playable_level_has_nothing_to_say() {
+        // standing right on top of a platform isnt being inside it
+        assert_eq!(playable().validate(), vec![]);
+    }
+
+    #[test]
+    fn broken_geometry_is_reported_by_index() {
+        let mut level = playable();
+        level.add_platform(Platform::new(vec2!(f32::NAN, 0.0), vec2!(10.0, 10.0)));
+        level.add_platform(Platform::new(vec2!(0.0, 0.0), vec2!(0.0, 10.0)));
+        level.add_platform(Platform::with_friction(vec2!(0.0, -100.0), vec2!(10.0, 10.0), -1.0));
+        level.add_platform(Platform::new(vec2!(0.0, 500.0), vec2!(20.0, 20.0)));
+        level.add_entity(Entity::new(EntityKind::Spikes, vec2!(0.0, 0.0), vec2!(f32::INFINITY, 1.0)));
+
+        let found = level
+            .validate()
+            .into_iter()
+            .map(|d| (d.severity, d.subject))
+            .collect::<Vec<(Severity, Subject)>>();
+
+        assert_eq!(found, vec![
+            (Severity::Error, Subject::Platform(1)),
+            (Severity::Warning, Subject::Platform(2)),
+            (Severity::Error, Subject::Platform(3)),
+            (Severity::Error, Subject::Platform(4)),
+            (Severity::Error, Subject::Entity(1)),
+        ]);
+    }
+
+    #[test]
+    fn starting_under_the_death_line_is_an_error() {
+        let mut level = playable();
+        level.set_death_y(Some(500.0));
+
+        let diagnostics = level.validate();
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].subject, Subject::PlayerStart);
+        assert!(diagnostics[0].is_error());
+    }
+
+    #[test]
+    fn medals_out_of_order_get_a_warning() {
+        let mut level = playable();
+        let meta = level.get_mut_meta();
+        meta.set_medal_time(Medal::Gold, Some(10.0));
+        meta.set_medal_time(Medal::Bronze, Some(20.0));
+        // a gap in the middle is fine
+        assert_eq!(level.validate(), vec![]);
+
+        level.get_mut_meta().set_medal_time(Medal::Silver, Some(8.0));
+        let diagnostics = level.validate();
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].subject, Subject::Level);
+        assert!(!diagnostics[0].is_error());
+    }
+
+    #[test]
+    fn levels_without_a_goal_get_a_warning() {
+        let level = Level::headless(vec![Platform::new(vec2!(0.0, 600.0), vec2!(600.0, 50.0))]);
+        let diagnostics = level.validate();
+
+        assert_eq!(diagnostics.len(), 1);
+        assert!(!diagnostics[0].is_error());
+        assert_eq!(diagnostics[0].to_string(), "warning: level has no goal so it can never be finished");
+    }
+}
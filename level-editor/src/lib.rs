@@ -1,3 +1,6 @@
+pub mod entity;
 pub mod level;
 mod tools;
+mod meta_panel;
+mod diagnostics_panel;
 pub mod editor;
\ No newline at end of file
@@ -0,0 +1,193 @@
+use std::fmt::Debug;
+
+use bottomless_pit::colour::Colour;
+use bottomless_pit::engine_handle::Engine;
+use bottomless_pit::input::{Key, ModifierKeys, MouseKey};
+use bottomless_pit::material::{Material, MaterialBuilder};
+use bottomless_pit::render::RenderInformation;
+use bottomless_pit::text::TextMaterial;
+use bottomless_pit::vec2;
+use bottomless_pit::vectors::Vec2;
+use utils::collision;
+use utils::keys::{typed_char, ALL_KEYS};
+
+use crate::level::{Difficulty, LevelMeta, Medal};
+
+const PANEL_POS: Vec2<f32> = vec2!(20.0, 20.0);
+const ROW_SIZE: Vec2<f32> = vec2!(460.0, 28.0);
+const ROW_GAP: f32 = 4.0;
+// how much a click moves a medal time, shift clicks move it 10 times as much
+const MEDAL_STEP: f32 = 0.5;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Field {
+    Name,
+    Author,
+    Description,
+    Difficulty,
+    MedalTime(Medal),
+}
+
+const FIELDS: [Field; 7] = [
+    Field::Name,
+    Field::Author,
+    Field::Description,
+    Field::Difficulty,
+    Field::MedalTime(Medal::Gold),
+    Field::MedalTime(Medal::Silver),
+    Field::MedalTime(Medal::Bronze),
+];
+
+impl Field {
+    fn text<'a>(&self, meta: &'a mut LevelMeta) -> Option<&'a mut String> {
+        match self {
+            Self::Name => Some(&mut meta.name),
+            Self::Author => Some(&mut meta.author),
+            Self::Description => Some(&mut meta.description),
+            Self::Difficulty | Self::MedalTime(_) => None,
+        }
+    }
+
+    fn label(&self, meta: &LevelMeta) -> String {
+        let time = |medal| match meta.get_medal_time(medal) {
+            Some(secs) => format!("{:.2}s", secs),
+            None => String::from("none"),
+        };
+
+        match self {
+            Self::Name => format!("name: {}", meta.name),
+            Self::Author => format!("author: {}", meta.author),
+            Self::Description => format!("description: {}", meta.description),
+            Self::Difficulty => format!("difficulty: {}", meta.difficulty.map_or("unrated", |d| d.name())),
+            Self::MedalTime(medal) => format!("{}: {}", medal.name(), time(*medal)),
+        }
+    }
+}
+
+/// edits the level metadata. click a text row then type, backspace or right click deletes a letter.
+/// click difficulty to go up a tier, left and right click medal times to add and take time
+pub(crate) struct MetaPanel {
+    material: Material,
+    rows: Vec<TextMaterial>,
+    focused: Option<Field>,
+}
+
+impl MetaPanel {
+    pub fn new(meta: &LevelMeta, engine: &mut Engine) -> Self {
+        let rows = FIELDS
+            .iter()
+            .map(|_| TextMaterial::new("", Colour::BLACK, 16.0, 20.0, engine))
+            .collect();
+
+        let mut panel = Self {
+            material: MaterialBuilder::new().build(engine),
+            rows,
+            focused: None,
+        };
+        panel.refresh(meta, engine);
+
+        panel
+    }
+
+    /// true while typing so letter keys dont switch tools or close the panel
+    pub fn is_typing(&self) -> bool {
+        self.focused.is_some()
+    }
+
+    pub fn update(&mut self, meta: &mut LevelMeta, engine: &mut Engine) {
+        let mouse_pos = engine.get_mouse_position();
+        let shift = engine.check_modifiers(ModifierKeys::Shift);
+        let left = engine.is_mouse_key_pressed(MouseKey::Left);
+        let right = engine.is_mouse_key_pressed(MouseKey::Right);
+        let mut changed = false;
+
+        if left || right {
+            let clicked = FIELDS
+                .iter()
+                .enumerate()
+                .find(|(idx, _)| collision::point_in_rect(mouse_pos, row_pos(*idx), ROW_SIZE))
+                .map(|(_, field)| *field);
+
+            self.focused = clicked.filter(|f| f.text(meta).is_some());
+            changed = true;
+
+            match clicked {
+                Some(Field::Difficulty) if left => meta.difficulty = Difficulty::harder(meta.difficulty),
+                Some(Field::MedalTime(medal)) => {
+                    let step = if shift { MEDAL_STEP * 10.0 } else { MEDAL_STEP };
+                    let step = if left { step } else { -step };
+                    let secs = meta.get_medal_time(medal).unwrap_or(0.0) + step;
+                    meta.set_medal_time(medal, Some(secs));
+                },
+                Some(field) if right => {
+                    if let Some(text) = field.text(meta) {
+                        text.pop();
+                    }
+                },
+                _ => {},
+            }
+        }
+
+        // ctrl is for shortcuts like saving, not typing
+        let typing_into = self.focused.and_then(|f| f.text(meta));
+        if let Some(text) = typing_into.filter(|_| !engine.check_modifiers(ModifierKeys::Ctrl)) {
+            for key in ALL_KEYS.into_iter().filter(|k| engine.is_key_pressed(*k)) {
+                if let Some(c) = typed_char(key, shift) {
+                    text.push(c);
+                    changed = true;
+                }
+            }
+
+            if engine.is_key_pressed(Key::Backspace) {
+                changed |= text.pop().is_some();
+            }
+        }
+
+        if changed {
+            self.refresh(meta, engine);
+        }
+    }
+
+    fn refresh(&mut self, meta: &LevelMeta, engine: &mut Engine) {
+        for (field, row) in FIELDS.iter().zip(&mut self.rows) {
+            let mut label = field.label(meta);
+            if self.focused == Some(*field) {
+                label.push('_');
+            }
+
+            row.set_text(&label, Colour::BLACK, engine);
+            row.prepare(engine);
+        }
+    }
+
+    pub fn draw<'p, 'o>(&'o mut self, renderer: &mut RenderInformation<'p, 'o>) where 'o: 'p {
+        for (idx, field) in FIELDS.iter().enumerate() {
+            let colour = if self.focused == Some(*field) {
+                Colour::from_rgba(255.0, 240.0, 180.0, 0.95)
+            } else {
+                Colour::from_rgba(230.0, 230.0, 230.0, 0.9)
+            };
+            self.material.add_rectangle(row_pos(idx), ROW_SIZE, colour, renderer);
+        }
+        self.material.draw(renderer);
+
+        for (idx, row) in self.rows.iter_mut().enumerate() {
+            row.add_instance(row_pos(idx) + vec2!(6.0, 4.0), Colour::WHITE, renderer);
+            row.draw(renderer);
+        }
+    }
+}
+
+impl Debug for MetaPanel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f
+            .debug_struct("MetaPanel")
+            .field("rows", &self.rows.iter().map(|r| r.get_text()).collect::<Vec<&str>>())
+            .field("focused", &self.focused)
+            .finish()
+    }
+}
+
+fn row_pos(idx: usize) -> Vec2<f32> {
+    vec2!(PANEL_POS.x, PANEL_POS.y + idx as f32 * (ROW_SIZE.y + ROW_GAP))
+}
@@ -8,7 +8,8 @@ use bottomless_pit::vectors::Vec2;
 use utils::collision;
 
 use crate::editor::EditorContext;
-use crate::level::Platform;
+use crate::entity::{Entity, EntityKind};
+use crate::level::{Platform, PLAYER_SIZE};
 
 pub trait Tool {
     fn on_click(&mut self, mouse_pos: Vec2<f32>, editor: &mut EditorContext);
@@ -213,4 +214,161 @@ impl Tool for MoveTool {
                 .for_each(|(pos, size)| material.add_rectangle(pos, size, Colour::from_rgba(255.0, 255.0, 255.0, 0.5), &renderer));
         }
     }
-}
\ No newline at end of file
+}
+
+/// clicking a platform cycles it between normal, ice and sticky
+#[derive(Debug)]
+pub struct FrictionTool;
+
+impl FrictionTool {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Tool for FrictionTool {
+    fn on_click(&mut self, mouse_pos: Vec2<f32>, editor: &mut EditorContext) {
+        // topmost platform is the last one drawn
+        let clicked = editor
+            .get_level()
+            .get_platforms()
+            .iter()
+            .enumerate()
+            .rev()
+            .find(|(_, p)| collision::point_in_rect(mouse_pos, p.pos, p.size))
+            .map(|(idx, p)| (idx, p.next_surface()));
+
+        if let Some((idx, friction)) = clicked {
+            editor.get_mut_level().set_platform_friction(idx, friction);
+        }
+    }
+
+    fn on_mouse_release(&mut self, _: Vec2<f32>, _: &mut EditorContext) {}
+
+    fn can_switch(&self) -> bool {
+        true
+    }
+
+    fn update(&mut self, _: &mut Engine, _: &mut EditorContext) {}
+
+    fn draw(&self, _: &mut Material, _: &EditorContext, _: &mut RenderInformation) {}
+}
+
+/// drag out a zone to place an entity, right click one to remove it
+#[derive(Debug)]
+pub struct EntityTool {
+    kind: EntityKind,
+    mouse_pressed_pos: Vec2<f32>,
+    preview: Option<Entity>,
+}
+
+impl EntityTool {
+    pub fn new(kind: EntityKind) -> Self {
+        Self {
+            kind,
+            mouse_pressed_pos: vec2!(0.0),
+            preview: None,
+        }
+    }
+}
+
+impl Tool for EntityTool {
+    fn on_click(&mut self, mouse_pos: Vec2<f32>, _: &mut EditorContext) {
+        self.mouse_pressed_pos = mouse_pos;
+    }
+
+    fn on_mouse_release(&mut self, _: Vec2<f32>, editor: &mut EditorContext) {
+        if let Some(entity) = self.preview.take() {
+            if entity.size.x != 0.0 && entity.size.y != 0.0 {
+                editor.get_mut_level().add_entity(entity);
+            }
+        }
+    }
+
+    fn can_switch(&self) -> bool {
+        self.preview.is_none()
+    }
+
+    fn init(&mut self, editor: &mut EditorContext) {
+        editor.selection = vec![];
+    }
+
+    fn update(&mut self, engine: &mut Engine, editor: &mut EditorContext) {
+        let mouse_pos = engine.get_mouse_position();
+
+        if engine.is_mouse_key_down(MouseKey::Left) {
+            self.preview = Some(Entity::from_corners(self.kind, self.mouse_pressed_pos, mouse_pos));
+        }
+
+        if engine.is_mouse_key_pressed(MouseKey::Right) {
+            let clicked = editor
+                .get_level()
+                .get_entities()
+                .iter()
+                .rposition(|e| collision::point_in_rect(mouse_pos, e.pos, e.size));
+
+            if let Some(idx) = clicked {
+                editor.get_mut_level().remove_entity(idx);
+            }
+        }
+    }
+
+    fn draw(&self, material: &mut Material, _: &EditorContext, renderer: &mut RenderInformation) {
+        if let Some(e) = &self.preview {
+            e.draw(material, renderer);
+        }
+    }
+}
+
+/// click to put the player start under the mouse or drag the existing marker around
+#[derive(Debug)]
+pub struct PlayerStartTool {
+    // where on the marker it was grabbed, Some while dragging
+    grab_offset: Option<Vec2<f32>>,
+}
+
+impl PlayerStartTool {
+    pub fn new() -> Self {
+        Self {
+            grab_offset: None,
+        }
+    }
+}
+
+impl Tool for PlayerStartTool {
+    fn on_click(&mut self, mouse_pos: Vec2<f32>, editor: &mut EditorContext) {
+        let start = editor.get_level().get_player_start();
+
+        let offset = if collision::point_in_rect(mouse_pos, start, PLAYER_SIZE) {
+            mouse_pos - start
+        } else {
+            // centered on the mouse when clicking somewhere new
+            let offset = vec2!(PLAYER_SIZE.x / 2.0, PLAYER_SIZE.y / 2.0);
+            editor.get_mut_level().set_player_start(mouse_pos - offset);
+            offset
+        };
+
+        self.grab_offset = Some(offset);
+    }
+
+    fn on_mouse_release(&mut self, _: Vec2<f32>, _: &mut EditorContext) {
+        self.grab_offset = None;
+    }
+
+    fn can_switch(&self) -> bool {
+        self.grab_offset.is_none()
+    }
+
+    fn init(&mut self, editor: &mut EditorContext) {
+        editor.selection = vec![];
+    }
+
+    fn update(&mut self, engine: &mut Engine, editor: &mut EditorContext) {
+        if let Some(offset) = self.grab_offset {
+            editor.get_mut_level().set_player_start(engine.get_mouse_position() - offset);
+        }
+    }
+
+    // the marker is always drawn by the editor so theres nothing extra here
+    fn draw(&self, _: &mut Material, _: &EditorContext, _: &mut RenderInformation) {}
+}
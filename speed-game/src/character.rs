@@ -1,54 +1,87 @@
-use bottomless_pit::colour::Colour;
-use bottomless_pit::engine_handle::Engine;
-use bottomless_pit::input::Key;
-use bottomless_pit::material::{Material, MaterialBuilder};
-use bottomless_pit::render::RenderInformation;
-use bottomless_pit::texture::{SamplerType, Texture};
 use bottomless_pit::vec2;
 use bottomless_pit::vectors::Vec2;
 
-use utils::collision::point_in_rect;
+use utils::collision::{rect_overlaps, swept_rect, Side};
 use level_editor::level::{Level, Platform};
+pub use level_editor::level::PLAYER_SIZE;
+
+use crate::input::{Action, InputState};
+use crate::jump_assist::JumpAssist;
+use crate::movement::MovementProfile;
+use crate::state::{check_transition, TransitionContext, TransitionError};
+pub use crate::state::{PlayerState, TransReason};
+use crate::timestep::lerp;
+
+// how far bellow the feet or beside the body we look for something to stand on or slide down
+const GROUND_PROBE: f32 = 0.5;
+// a corner can need one resolution per axis plus a spare
+const MAX_COLLISION_STEPS: usize = 3;
+
+/// tuning for the dash ability
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DashConfig {
+    pub speed: f32,
+    /// how long the dash lasts in seconds
+    pub duration: f32,
+    /// seconds after a dash ends before another one can start
+    pub cooldown: f32,
+    /// dashes available before touching the ground again, ground dashes are free
+    pub air_charges: u32,
+}
 
-const PLAYER_ACCELERATION: f32 = 190.0;
-const PLAYER_DECLERATION: f32 = 100.0;
-const PLAYER_TURN_SPEED: f32 = 250.0;
-const PLAYER_MAX_SPEED: f32 = 200.0;
-const PLAYER_MAX_AIRSPEED: f32 = 200.0;
-const PLAYER_AIR_ACCEL: f32 = 145.0;
-const PLAYER_AIR_DECEL: f32 = 50.0;
-const PLAYER_AIR_TURN_SPEED: f32 = 150.0;
-
-const PLAYER_FALL_ACCELERATION: f32 = 80.0;
-const MAX_FALL_SPEED: f32 = 200.0;
-const PLAYER_SIZE: Vec2<f32> = vec2!(96.0, 114.0);
+impl Default for DashConfig {
+    fn default() -> Self {
+        Self {
+            speed: 450.0,
+            duration: 0.15,
+            cooldown: 0.3,
+            air_charges: 1,
+        }
+    }
+}
 
+/// the players physics and state, drawing is left to `PlayerSprite` so this can run without a window
+#[derive(Clone, Debug)]
 pub struct Character {
     pos: Vec2<f32>,
+    // where we were at the start of the last tick, used to interpolate rendering
+    prev_pos: Vec2<f32>,
     speed: Vec2<f32>,
     size: Vec2<f32>,
     fastest_y: f32,
-    material: Material,
     state: PlayerState,
     friction: f32,
+    contacts: Contacts,
+    wall_side: Option<Side>,
+    facing: f32,
+    profile: MovementProfile,
+    dash_dir: Vec2<f32>,
+    dash_timer: f32,
+    dash_cooldown: f32,
+    dash_charges: u32,
+    jump_assist: JumpAssist,
 }
 
 impl Character {
-    pub fn new(engine: &mut Engine) -> Self {
-        let texture = Texture::new_with_sampler(engine, "speed-game/assets/shork.png", SamplerType::NearestNeighbor);
-
-        let material = MaterialBuilder::new()
-            .add_texture(texture)
-            .build(engine);
-
+    /// a player standing still at `pos`, usually the levels player start
+    pub fn new(profile: MovementProfile, pos: Vec2<f32>) -> Self {
         Self {
-            pos: Vec2{x: 0.0, y: -200.0},
+            pos,
+            prev_pos: pos,
             speed: Vec2{x: 0.0, y: 0.0},
             size: PLAYER_SIZE,
-            material,
             fastest_y: 0.0,
             state: PlayerState::Falling,
             friction: 1.0,
+            contacts: Contacts::default(),
+            wall_side: None,
+            facing: 1.0,
+            profile,
+            dash_dir: vec2!(0.0),
+            dash_timer: 0.0,
+            dash_cooldown: 0.0,
+            dash_charges: profile.dash.air_charges,
+            jump_assist: JumpAssist::new(profile.jump_assist),
         }
     }
 
@@ -60,6 +93,16 @@ impl Character {
         vec2!(self.pos.x + self.size.x / 2.0, self.pos.y + self.size.y / 2.0)
     } 
 
+    /// position blended between the last two ticks, `alpha` comes from the fixed timestep
+    pub fn get_interpolated_pos(&self, alpha: f32) -> Vec2<f32> {
+        vec2!(lerp(self.prev_pos.x, self.pos.x, alpha), lerp(self.prev_pos.y, self.pos.y, alpha))
+    }
+
+    pub fn get_interpolated_center(&self, alpha: f32) -> Vec2<f32> {
+        let pos = self.get_interpolated_pos(alpha);
+        vec2!(pos.x + self.size.x / 2.0, pos.y + self.size.y / 2.0)
+    }
+
     pub fn get_size(&self) -> Vec2<f32> {
         self.size
     }
@@ -72,72 +115,157 @@ impl Character {
         self.state
     }
 
-    pub fn update(&mut self, dt: f32, engine: &Engine, level: &Level) {
+    pub fn get_contacts(&self) -> Contacts {
+        self.contacts
+    }
+
+    pub fn get_dash_charges(&self) -> u32 {
+        self.dash_charges
+    }
+
+    pub fn get_profile(&self) -> &MovementProfile {
+        &self.profile
+    }
+
+    /// swaps every movement number at once, e.g. when an upgrade is picked up or the file is reloaded
+    pub fn set_profile(&mut self, profile: MovementProfile) {
+        self.profile = profile;
+        self.dash_charges = self.dash_charges.min(profile.dash.air_charges);
+        self.jump_assist.set_config(profile.jump_assist);
+    }
+
+    /// advances the player by one fixed tick, `input` should already be advanced to this tick
+    pub fn update(&mut self, dt: f32, input: &InputState, level: &Level) {
+        self.prev_pos = self.pos;
+        if self.state == PlayerState::Dead {
+            return;
+        }
+
+        self.dash_cooldown = (self.dash_cooldown - dt).max(0.0);
+
+        let move_x = input.horizontal();
+        if move_x != 0.0 {
+            self.facing = move_x;
+        }
+
+        if input.is_pressed(Action::Dash) {
+            self.dash_action(input);
+        }
+
+        self.jump_assist.tick(dt, self.state == PlayerState::Grounded, input.is_pressed(Action::Jump));
+
+        if self.jump_assist.can_jump() && matches!(self.state, PlayerState::Grounded | PlayerState::Falling) {
+            self.jump_action(input);
+        }
+
         match self.state {
-            PlayerState::Grounded => self.grounded_movement(dt, engine),
-            PlayerState::Falling => self.air_movment(dt, engine),
-            PlayerState::Jumping => self.jumping_movement(dt, engine),
-            _ => unimplemented!(),
+            PlayerState::Grounded => self.grounded_movement(dt, input),
+            PlayerState::Falling => self.air_movment(dt, input),
+            PlayerState::Jumping => self.jumping_movement(dt, input),
+            PlayerState::WallSliding => self.wall_slide_movement(dt, input),
+            PlayerState::Dashing => self.dash_movement(dt),
+            PlayerState::Dead => {},
         }
 
-        self.pos += self.speed.scale(dt);
+        self.contacts = self.move_and_collide(dt, level.get_platforms());
+        self.check_wall_slide(input);
 
-        let not_collided = level.get_platforms().iter().all(|p| !self.bottom_collision(p));
-        if not_collided && self.state != PlayerState::Falling {
-            self.request_transition(PlayerState::Falling, TransReason::NothingBellow);
+        if self.contacts.top && self.state == PlayerState::Jumping {
+            let _ = self.request_transition(PlayerState::Falling, TransReason::CeilingCollision);
+        }
+
+        if !self.contacts.bottom && self.state != PlayerState::Falling {
+            let _ = self.request_transition(PlayerState::Falling, TransReason::NothingBellow);
+        }
+
+        if self.state == PlayerState::Dashing && self.dash_timer <= 0.0 {
+            let next = if self.contacts.bottom {
+                PlayerState::Grounded
+            } else {
+                PlayerState::Falling
+            };
+
+            let _ = self.request_transition(next, TransReason::DashEnd);
         }
     }
 
-    pub fn request_transition(&mut self, new_state: PlayerState, reason: TransReason) {
-        match (self.state, new_state, reason) {
-            (PlayerState::Grounded, PlayerState::Grounded, _) => {},
-            (PlayerState::Grounded, PlayerState::Jumping, TransReason::JumpStart) => {
-                self.state = PlayerState::Jumping;
-            },
-            (PlayerState::Grounded, PlayerState::Falling, TransReason::NothingBellow) => {
-                self.state = PlayerState::Falling;
-            },
-            (PlayerState::Jumping, PlayerState::Jumping, _) => {},
-            (PlayerState::Jumping, PlayerState::Falling, TransReason::NothingBellow) => {
-                if self.speed.y > 0.0 {
-                    self.state = PlayerState::Falling;
-                }
-            },
-            (PlayerState::Jumping, PlayerState::Grounded, TransReason::GroudCollision) => {
-                self.state = PlayerState::Grounded;
-            },
-            (PlayerState::Falling, PlayerState::Falling, _) => {},
-            (PlayerState::Falling, PlayerState::Grounded, TransReason::GroudCollision) => {
-                self.state = PlayerState::Grounded;
-            },
-            (_, _, _) => todo!()
+    /// stops the player where they are, they stay dead until `respawn`
+    pub fn kill(&mut self) {
+        if self.request_transition(PlayerState::Dead, TransReason::Killed).is_ok() {
+            self.speed = vec2!(0.0);
         }
     }
 
-    fn grounded_movement(&mut self, dt: f32, engine: &Engine) {
-        self.horizontal_movment(engine, dt, [PLAYER_TURN_SPEED, PLAYER_ACCELERATION, PLAYER_DECLERATION, PLAYER_MAX_SPEED]);
+    /// a fresh player at `pos` with the same movement profile
+    pub fn respawn(&mut self, pos: Vec2<f32>) {
+        *self = Self::new(self.profile, pos);
+    }
 
-        if engine.is_key_down(Key::Space) {
-            self.jump_action();
-        }
+    /// asks to move to `new_state`. the transition table in `state.rs` decides if that is allowed,
+    /// requests with no rule at all are logged since they mean something is asking for nonsense
+    pub fn request_transition(&mut self, new_state: PlayerState, reason: TransReason) -> Result<(), TransitionError> {
+        let ctx = TransitionContext {
+            speed: self.speed,
+        };
 
-        // caps both backwards and forwards speed
-        self.speed.x = self.speed.x.min(PLAYER_MAX_SPEED);
-        self.speed.x = self.speed.x.max(-PLAYER_MAX_SPEED);
+        let next = check_transition(self.state, new_state, reason, &ctx).inspect_err(|e| {
+            if matches!(e, TransitionError::NotAllowed { .. }) {
+                eprintln!("rejected player transition: {}", e);
+            }
+        })?;
 
-        self.fastest_y = self.speed.y.max(self.fastest_y);
+        if next != self.state {
+            let previous = self.state;
+            self.state = next;
+            self.on_transition(previous);
+        }
+
+        Ok(())
     }
 
-    fn horizontal_movment(&mut self, engine: &Engine, dt: f32, constants: [f32; 4]) {
-        let mut move_x: f32 = 0.0;
+    fn on_transition(&mut self, previous: PlayerState) {
+        if previous == PlayerState::WallSliding {
+            self.wall_side = None;
+        }
+
+        if previous == PlayerState::Dashing {
+            self.dash_cooldown = self.profile.dash.cooldown;
+            // come out of the dash at normal air speed instead of keeping the burst, only sideways
+            // since gravity takes care of the vertical speed
+            self.speed.x = self.speed.x.clamp(-self.profile.max_air_speed, self.profile.max_air_speed);
+        }
 
-        if engine.is_key_down(Key::D) {
-            move_x += 1.0;
+        // only once actually on the ground, landing in the middle of a dash doesnt count
+        if self.state == PlayerState::Grounded {
+            self.dash_charges = self.profile.dash.air_charges;
         }
 
-        if engine.is_key_down(Key::A) {
-            move_x -= 1.0;
+        if self.state == PlayerState::Dashing {
+            if previous != PlayerState::Grounded {
+                self.dash_charges = self.dash_charges.saturating_sub(1);
+            }
+
+            self.dash_timer = self.profile.dash.duration;
         }
+    }
+
+    fn grounded_movement(&mut self, dt: f32, input: &InputState) {
+        // friction changes how quickly the player speeds up, slows down and turns but not the top speed
+        let mut constants = self.profile.ground_constants();
+        constants[0] *= self.friction;
+        constants[1] *= self.friction;
+        constants[2] *= self.friction;
+        self.horizontal_movment(input, dt, constants);
+
+        // caps both backwards and forwards speed
+        self.speed.x = self.speed.x.min(self.profile.max_speed);
+        self.speed.x = self.speed.x.max(-self.profile.max_speed);
+
+        self.fastest_y = self.speed.y.max(self.fastest_y);
+    }
+
+    fn horizontal_movment(&mut self, input: &InputState, dt: f32, constants: [f32; 4]) {
+        let move_x = input.horizontal();
 
         // if they dont have the same sign ur turning
         let max_speed = if move_x != 0.0 && self.speed.x.is_sign_positive() != move_x.is_sign_positive() {
@@ -153,53 +281,204 @@ impl Character {
         }
     }
 
-    fn air_movment(&mut self, dt: f32, engine: &Engine) {
-        self.speed.y += PLAYER_FALL_ACCELERATION * dt;
-        self.speed.y = self.speed.y.min(MAX_FALL_SPEED);
+    fn air_movment(&mut self, dt: f32, input: &InputState) {
+        self.speed.y += self.profile.fall_acceleration * dt;
+        self.speed.y = self.speed.y.min(self.profile.max_fall_speed);
 
         // if self.speed.y >= -20.0 && self.speed.y <= 20.0 {
         //     println!("top of jump?");
         // } else {
         //     println!("normal jump");
         // }
-        self.horizontal_movment(engine, dt, [PLAYER_AIR_TURN_SPEED, PLAYER_AIR_ACCEL, PLAYER_AIR_DECEL, PLAYER_MAX_AIRSPEED])
+        self.horizontal_movment(input, dt, self.profile.air_constants())
     }
 
-    fn jumping_movement(&mut self, dt: f32, engine: &Engine) {
-        if engine.is_key_released(Key::Space) {
-            self.speed.y = f32::min(self.speed.y + 40.0, 0.0);
+    fn jumping_movement(&mut self, dt: f32, input: &InputState) {
+        // checked per tick rather than per frame so the cut happens exactly once
+        if input.is_released(Action::Jump) {
+            self.cut_jump();
         }
 
-        self.air_movment(dt, engine);
+        self.air_movment(dt, input);
     }
 
-    fn jump_action(&mut self) {
-        if self.state == PlayerState::Grounded {
-            self.speed.y = -(2.0_f32 * PLAYER_FALL_ACCELERATION * 100.0).sqrt();
+    fn cut_jump(&mut self) {
+        self.speed.y = f32::min(self.speed.y + self.profile.jump_cut, 0.0);
+    }
+
+    /// only called once `jump_assist` says a jump is allowed, which covers both
+    /// standing on the ground and having just run off it
+    fn jump_action(&mut self, input: &InputState) {
+        self.speed.y = self.profile.jump_speed();
+        self.jump_assist.consume();
+
+        let _ = self.request_transition(PlayerState::Jumping, TransReason::JumpStart);
+
+        // a buffered jump can fire after the button already came back up, that release
+        // was never seen while jumping so cut it here instead of giving a full jump
+        if !input.is_down(Action::Jump) {
+            self.cut_jump();
         }
+    }
 
-        self.request_transition(PlayerState::Jumping, TransReason::JumpStart);
+    fn dash_action(&mut self, input: &InputState) {
+        let can_dash = self.state != PlayerState::Dashing &&
+            self.dash_cooldown <= 0.0 &&
+            (self.state == PlayerState::Grounded || self.dash_charges > 0);
+
+        if !can_dash {
+            return;
+        }
+
+        self.dash_dir = match (input.horizontal(), input.vertical()) {
+            (x, y) if x == 0.0 && y == 0.0 => vec2!(self.facing, 0.0),
+            (x, y) if x != 0.0 && y != 0.0 => vec2!(x, y).scale(std::f32::consts::FRAC_1_SQRT_2),
+            (x, y) => vec2!(x, y),
+        };
+
+        let _ = self.request_transition(PlayerState::Dashing, TransReason::DashStart);
+    }
+
+    fn dash_movement(&mut self, dt: f32) {
+        self.dash_timer -= dt;
+        self.speed = self.dash_dir.scale(self.profile.dash.speed);
     }
 
-    fn bottom_collision(&mut self, platform: &Platform) -> bool {
-        let bottom_left = point_in_rect(vec2!(self.pos.x, self.pos.y + self.size.y), platform.pos, platform.size);
-        let bottom_right = point_in_rect(vec2!(self.pos.x + self.size.x, self.pos.y + self.size.y), platform.pos, platform.size);
+    fn wall_slide_movement(&mut self, dt: f32, input: &InputState) {
+        self.speed.y += self.profile.fall_acceleration * dt;
+        self.speed.y = self.speed.y.min(self.profile.wall_slide_speed);
 
-        if bottom_left || bottom_right {
-            self.request_transition(PlayerState::Grounded, TransReason::GroudCollision);
-            self.pos.y = platform.pos.y - self.size.y;
-            self.speed.y = 0.0;
-            self.friction = platform.friction;
-            true
+        // a press from just before grabbing the wall still counts
+        if self.jump_assist.is_buffered() {
+            self.jump_assist.consume();
+            self.wall_jump_action(input);
         } else {
-            false
+            self.horizontal_movment(input, dt, self.profile.air_constants());
         }
     }
 
-    pub fn draw<'p, 'o>(&'o mut self, renderer: &mut RenderInformation<'p, 'o>) where 'o: 'p {
-        self.material.add_rectangle(self.pos, self.size, Colour::WHITE, &renderer);
+    fn wall_jump_action(&mut self, input: &InputState) {
+        let away = match self.wall_side {
+            Some(Side::Left) => 1.0,
+            Some(Side::Right) => -1.0,
+            _ => 0.0,
+        };
+
+        self.speed = vec2!(away * self.profile.wall_jump_push, self.profile.jump_speed());
+        let _ = self.request_transition(PlayerState::Jumping, TransReason::WallJump);
 
-        self.material.draw(renderer);
+        if !input.is_down(Action::Jump) {
+            self.cut_jump();
+        }
+    }
+
+    /// grabs onto a wall when pushing into it mid air and lets go once the player stops
+    fn check_wall_slide(&mut self, input: &InputState) {
+        let move_x = input.horizontal();
+
+        let held_side = if move_x < 0.0 {
+            Some(Side::Left)
+        } else if move_x > 0.0 {
+            Some(Side::Right)
+        } else {
+            None
+        };
+        let pushed_side = held_side.filter(|side| self.contacts.touching(*side));
+
+        match (self.state, pushed_side) {
+            (PlayerState::Falling | PlayerState::Jumping, Some(side)) if !self.contacts.bottom => {
+                let _ = self.request_transition(PlayerState::WallSliding, TransReason::WallCollision);
+                if self.state == PlayerState::WallSliding {
+                    self.wall_side = Some(side);
+                }
+            },
+            (PlayerState::WallSliding, None) => {
+                let _ = self.request_transition(PlayerState::Falling, TransReason::WallRelease);
+            },
+            _ => {},
+        }
+    }
+
+    /// sweeps the player along its velocity, stopping at the first platform in the way and
+    /// sliding along it with whatever movement is left. returns every side of the player
+    /// that is touching something
+    fn move_and_collide(&mut self, dt: f32, platforms: &[Platform]) -> Contacts {
+        let mut contacts = Contacts::default();
+        let mut remaining = self.speed.scale(dt);
+
+        for _ in 0..MAX_COLLISION_STEPS {
+            let closest = platforms
+                .iter()
+                .filter_map(|p| swept_rect(self.pos, self.size, remaining, p.pos, p.size).map(|hit| (hit, p)))
+                .min_by(|(a, _), (b, _)| a.time.total_cmp(&b.time));
+
+            let Some((hit, platform)) = closest else {
+                self.pos += remaining;
+                break;
+            };
+
+            self.pos += remaining.scale(hit.time);
+            let left_over = remaining.scale(1.0 - hit.time);
+
+            // snap flush to the surface so float error cant build up between steps
+            let side = hit.side();
+            match side {
+                Side::Left => {
+                    self.pos.x = platform.pos.x + platform.size.x;
+                    self.speed.x = 0.0;
+                    remaining = vec2!(0.0, left_over.y);
+                },
+                Side::Right => {
+                    self.pos.x = platform.pos.x - self.size.x;
+                    self.speed.x = 0.0;
+                    remaining = vec2!(0.0, left_over.y);
+                },
+                Side::Top => {
+                    self.pos.y = platform.pos.y + platform.size.y;
+                    remaining = vec2!(left_over.x, 0.0);
+                },
+                Side::Bottom => {
+                    self.pos.y = platform.pos.y - self.size.y;
+                    self.friction = platform.friction;
+                    remaining = vec2!(left_over.x, 0.0);
+                },
+            }
+
+            contacts.set(side);
+        }
+
+        // standing still means we never move into the ground so look just bellow us instead
+        if !contacts.bottom && self.speed.y >= 0.0 {
+            let feet = vec2!(self.pos.x, self.pos.y + self.size.y);
+            if let Some(platform) = platforms.iter().find(|p| rect_overlaps(feet, vec2!(self.size.x, GROUND_PROBE), p.pos, p.size)) {
+                // the probe can find ground a little bellow the feet, close the gap so we dont hover
+                self.pos.y = platform.pos.y - self.size.y;
+                self.friction = platform.friction;
+                contacts.set(Side::Bottom);
+            }
+        }
+
+        // same for walls, sliding down one doesnt move us into it
+        let left_probe = vec2!(self.pos.x - GROUND_PROBE, self.pos.y);
+        let right_probe = vec2!(self.pos.x + self.size.x, self.pos.y);
+        let probe_size = vec2!(GROUND_PROBE, self.size.y);
+        if platforms.iter().any(|p| rect_overlaps(left_probe, probe_size, p.pos, p.size)) {
+            contacts.set(Side::Left);
+        }
+        if platforms.iter().any(|p| rect_overlaps(right_probe, probe_size, p.pos, p.size)) {
+            contacts.set(Side::Right);
+        }
+
+        if contacts.bottom {
+            self.speed.y = self.speed.y.min(0.0);
+            let _ = self.request_transition(PlayerState::Grounded, TransReason::GroudCollision);
+        }
+
+        if contacts.top {
+            self.speed.y = self.speed.y.max(0.0);
+        }
+
+        contacts
     }
 }
 
@@ -207,7 +486,7 @@ impl Character {
 // need to integrate this somehow
 fn move_towards(current: f32, target: f32, max_delta: f32) -> f32 {
     if (current-target).abs() <= max_delta {
-        return target
+        target
     } else {
         let sign = if (target-current).is_sign_positive() {
             1.0
@@ -215,22 +494,35 @@ fn move_towards(current: f32, target: f32, max_delta: f32) -> f32 {
             -1.0
         };
 
-        return current + sign * max_delta;
+        current + sign * max_delta
     }
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
-#[non_exhaustive]
-pub enum PlayerState {
-    Grounded,
-    Jumping,
-    Falling,
+/// which sides of the player touched a platform during the last update
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Contacts {
+    pub left: bool,
+    pub right: bool,
+    pub top: bool,
+    pub bottom: bool,
 }
 
-#[derive(Clone, Copy, Debug)]
-#[non_exhaustive]
-pub enum TransReason {
-    GroudCollision,
-    JumpStart,
-    NothingBellow,
-}
\ No newline at end of file
+impl Contacts {
+    pub fn touching(&self, side: Side) -> bool {
+        match side {
+            Side::Left => self.left,
+            Side::Right => self.right,
+            Side::Top => self.top,
+            Side::Bottom => self.bottom,
+        }
+    }
+
+    fn set(&mut self, side: Side) {
+        match side {
+            Side::Left => self.left = true,
+            Side::Right => self.right = true,
+            Side::Top => self.top = true,
+            Side::Bottom => self.bottom = true,
+        }
+    }
+}
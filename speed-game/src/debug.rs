@@ -1,13 +1,12 @@
-use bottomless_pit::input::Key;
 use bottomless_pit::material::{Material, MaterialBuilder};
 use bottomless_pit::vec2;
-use bottomless_pit::vectors::Vec2;
 use bottomless_pit::colour::Colour;
 use bottomless_pit::engine_handle::Engine;
 use bottomless_pit::text::TextMaterial;
 use bottomless_pit::render::RenderInformation;
 
 use crate::character::Character;
+use crate::input::{Action, Bindings};
 
 pub struct DebugText {
     text_mat: TextMaterial,
@@ -19,7 +18,7 @@ pub struct DebugText {
 
 impl DebugText {
     pub fn new(engine: &mut Engine) -> Self {
-        let text = String::from("pos: {}\nspeed: {}\ndt: {}\nframerate: {}\nplayerstate");
+        let text = String::from("pos: {}\nspeed: {}\ndt: {}\nframerate: {}\nplayerstate\ncontacts\ndashes");
 
         let line_numbers = text
             .bytes()
@@ -50,7 +49,9 @@ impl DebugText {
     pub fn update_player_info(&mut self, player: &Character) {
         self.replace_line(0, &format!("pos: {:?}", player.get_pos()));
         self.replace_line(1, &format!("speed: {:?}", player.get_speed()));
-        self.replace_line(4, &format!("playerstate: {:?}", player.get_state()))
+        self.replace_line(4, &format!("playerstate: {:?}", player.get_state()));
+        self.replace_line(5, &format!("contacts: {:?}", player.get_contacts()));
+        self.replace_line(6, &format!("dashes: {}/{}", player.get_dash_charges(), player.get_profile().dash.air_charges));
     }
 
     pub fn update_engine_info(&mut self, engine: &Engine, dt: f32) {
@@ -84,8 +85,8 @@ impl DebugText {
             .collect::<Vec<usize>>();
     }
 
-    pub fn prepare(&mut self, engine: &mut Engine) {
-        if engine.is_key_pressed(Key::F3) || engine.is_key_pressed(Key::P) {
+    pub fn prepare(&mut self, engine: &mut Engine, bindings: &Bindings) {
+        if bindings.is_pressed(engine, Action::ToggleDebug) {
             self.active = !self.active;
         }
 
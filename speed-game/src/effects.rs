@@ -0,0 +1,118 @@
+use bottomless_pit::colour::Colour;
+use bottomless_pit::engine_handle::Engine;
+use bottomless_pit::material::{Material, MaterialBuilder};
+use bottomless_pit::render::RenderInformation;
+use bottomless_pit::vec2;
+use bottomless_pit::vectors::Vec2;
+
+// how long the flash on a new checkpoint lasts in seconds
+const FLASH_TIME: f32 = 0.4;
+// how far past the checkpoint the flash grows on each side
+const FLASH_GROWTH: f32 = 24.0;
+// how long the burst where the player died lasts, a bit under the respawn delay
+const DEATH_TIME: f32 = 0.6;
+const DEATH_GROWTH: f32 = 48.0;
+
+/// lights up checkpoints once touched and flashes them when they first get activated.
+/// keeps its own copy of where they are so drawing doesnt need the level
+pub struct CheckpointEffects {
+    material: Material,
+    // position and size of every checkpoint touched since the last reset
+    active: Vec<(Vec2<f32>, Vec2<f32>)>,
+    // index into `active` and seconds left
+    flashes: Vec<(usize, f32)>,
+}
+
+impl CheckpointEffects {
+    pub fn new(engine: &mut Engine) -> Self {
+        Self {
+            material: MaterialBuilder::new().build(engine),
+            active: Vec::new(),
+            flashes: Vec::new(),
+        }
+    }
+
+    /// lights up the checkpoint covering `pos` and `size`
+    pub fn activate(&mut self, pos: Vec2<f32>, size: Vec2<f32>) {
+        self.flashes.push((self.active.len(), FLASH_TIME));
+        self.active.push((pos, size));
+    }
+
+    pub fn update(&mut self, dt: f32) {
+        for (_, time_left) in &mut self.flashes {
+            *time_left -= dt;
+        }
+        self.flashes.retain(|(_, t)| *t > 0.0);
+    }
+
+    pub fn reset(&mut self) {
+        self.active.clear();
+        self.flashes.clear();
+    }
+
+    /// draws over the level in world space
+    pub fn draw<'p, 'o>(&'o mut self, renderer: &mut RenderInformation<'p, 'o>) where 'o: 'p {
+        for (pos, size) in &self.active {
+            self.material.add_rectangle(*pos, *size, Colour::from_rgba(120.0, 200.0, 255.0, 0.35), renderer);
+        }
+
+        for (idx, time_left) in &self.flashes {
+            let (pos, size) = self.active[*idx];
+
+            // starts solid on the checkpoint and fades out as it grows
+            let t = 1.0 - time_left / FLASH_TIME;
+            let grow = FLASH_GROWTH * t;
+            let pos = vec2!(pos.x - grow, pos.y - grow);
+            let size = vec2!(size.x + grow * 2.0, size.y + grow * 2.0);
+            self.material.add_rectangle(pos, size, Colour::from_rgba(255.0, 255.0, 255.0, 0.8 * (1.0 - t)), renderer);
+        }
+
+        self.material.draw(renderer);
+    }
+}
+
+/// a red burst where the player died that fades out while they wait to respawn
+pub struct DeathEffect {
+    material: Material,
+    // where the player was and seconds left
+    burst: Option<(Vec2<f32>, Vec2<f32>, f32)>,
+}
+
+impl DeathEffect {
+    pub fn new(engine: &mut Engine) -> Self {
+        Self {
+            material: MaterialBuilder::new().build(engine),
+            burst: None,
+        }
+    }
+
+    /// starts the burst over the players box, a second death restarts it
+    pub fn trigger(&mut self, pos: Vec2<f32>, size: Vec2<f32>) {
+        self.burst = Some((pos, size, DEATH_TIME));
+    }
+
+    pub fn update(&mut self, dt: f32) {
+        if let Some((_, _, time_left)) = &mut self.burst {
+            *time_left -= dt;
+        }
+        self.burst = self.burst.filter(|(_, _, t)| *t > 0.0);
+    }
+
+    pub fn reset(&mut self) {
+        self.burst = None;
+    }
+
+    /// draws over the level in world space
+    pub fn draw<'p, 'o>(&'o mut self, renderer: &mut RenderInformation<'p, 'o>) where 'o: 'p {
+        let Some((pos, size, time_left)) = self.burst else {
+            return;
+        };
+
+        let t = 1.0 - time_left / DEATH_TIME;
+        let grow = DEATH_GROWTH * t;
+        let burst_pos = vec2!(pos.x - grow, pos.y - grow);
+        let burst_size = vec2!(size.x + grow * 2.0, size.y + grow * 2.0);
+        self.material.add_rectangle(burst_pos, burst_size, Colour::from_rgba(230.0, 40.0, 40.0, 0.7 * (1.0 - t)), renderer);
+        self.material.draw(renderer);
+    }
+}
@@ -0,0 +1,376 @@
+use std::fmt::Display;
+use std::path::{Path, PathBuf};
+
+use bottomless_pit::vec2;
+use bottomless_pit::vectors::Vec2;
+
+use level_editor::level::Level;
+
+use crate::character::{Character, PlayerState};
+use crate::timestep::{lerp, FIXED_DT};
+
+// SGGH in bytes
+const FILE_HEADER: [u8; 4] = [115, 103, 103, 104];
+// 2 starts recording when the timer does, 1 counted the idle ticks from spawn so it is dropped
+const VERSION: u16 = 2;
+// header + version + level hash + frame count
+const PREAMBLE_LEN: usize = 4 + 2 + 8 + 4;
+// x, y and the state
+const FRAME_LEN: usize = 4 + 4 + 1;
+// how far either side of the last match the split looks for the closest ghost frame
+const SPLIT_SEARCH_TICKS: usize = 240;
+
+/// where the player was at the end of one tick
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GhostFrame {
+    pub pos: Vec2<f32>,
+    pub state: PlayerState,
+}
+
+/// a whole run as positions, one frame per tick the timer was running
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Ghost {
+    level_hash: u64,
+    frames: Vec<GhostFrame>,
+}
+
+impl Ghost {
+    pub fn new(level: &Level) -> Self {
+        Self {
+            level_hash: level.content_hash(),
+            frames: Vec::new(),
+        }
+    }
+
+    pub fn record(&mut self, player: &Character) {
+        self.frames.push(GhostFrame {
+            pos: player.get_pos(),
+            state: player.get_state(),
+        });
+    }
+
+    pub fn get_frames(&self) -> &[GhostFrame] {
+        &self.frames
+    }
+
+    pub fn get_level_hash(&self) -> u64 {
+        self.level_hash
+    }
+
+    /// how long the run took in ticks, the same as the timers total
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    /// ghosts are stored one per level, named after the level so an edited level starts fresh
+    pub fn path_for(dir: &Path, level_hash: u64) -> PathBuf {
+        dir.join(format!("{:016x}.sggh", level_hash))
+    }
+
+    /// the saved best for `level` if there is one
+    pub fn load_best(dir: &Path, level: &Level) -> Option<Self> {
+        let path = Self::path_for(dir, level.content_hash());
+        match Self::load(&path) {
+            Ok(ghost) => Some(ghost),
+            Err(GhostError::Io(e)) if e.kind() == std::io::ErrorKind::NotFound => None,
+            Err(e) => {
+                eprintln!("ignoring ghost {}: {}", path.display(), e);
+                None
+            },
+        }
+    }
+
+    /// writes this run over the saved one if it was quicker, returns true if it did
+    pub fn save_if_best(&self, dir: &Path) -> Result<bool, std::io::Error> {
+        let path = Self::path_for(dir, self.level_hash);
+        let best = Self::load(&path).ok();
+
+        if best.is_some_and(|b| b.len() <= self.len()) {
+            return Ok(false);
+        }
+
+        std::fs::create_dir_all(dir)?;
+        self.save(&path)?;
+        Ok(true)
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut buffer = Vec::with_capacity(PREAMBLE_LEN + self.frames.len() * FRAME_LEN);
+        buffer.extend(FILE_HEADER);
+        buffer.extend(VERSION.to_le_bytes());
+        buffer.extend(self.level_hash.to_le_bytes());
+        buffer.extend((self.frames.len() as u32).to_le_bytes());
+
+        for frame in &self.frames {
+            buffer.extend(frame.pos.x.to_le_bytes());
+            buffer.extend(frame.pos.y.to_le_bytes());
+            buffer.push(state_to_byte(frame.state));
+        }
+
+        buffer
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, GhostError> {
+        if bytes.len() < PREAMBLE_LEN {
+            return Err(GhostError::Truncated);
+        }
+
+        if bytes[0..4] != FILE_HEADER {
+            return Err(GhostError::BadMagic);
+        }
+
+        let version = u16::from_le_bytes([bytes[4], bytes[5]]);
+        if version != VERSION {
+            return Err(GhostError::UnsupportedVersion(version));
+        }
+
+        let level_hash = u64::from_le_bytes(bytes[6..14].try_into().unwrap());
+        let frame_count = u32::from_le_bytes(bytes[14..18].try_into().unwrap()) as usize;
+
+        let data = &bytes[PREAMBLE_LEN..];
+        if data.len() != frame_count * FRAME_LEN {
+            return Err(GhostError::Truncated);
+        }
+
+        let frames = data
+            .chunks_exact(FRAME_LEN)
+            .map(|f| {
+                let x = f32::from_le_bytes([f[0], f[1], f[2], f[3]]);
+                let y = f32::from_le_bytes([f[4], f[5], f[6], f[7]]);
+                let state = state_from_byte(f[8]).ok_or(GhostError::BadState(f[8]))?;
+                Ok(GhostFrame { pos: vec2!(x, y), state })
+            })
+            .collect::<Result<Vec<GhostFrame>, GhostError>>()?;
+
+        Ok(Self {
+            level_hash,
+            frames,
+        })
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), std::io::Error> {
+        std::fs::write(path, self.to_bytes())
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, GhostError> {
+        let bytes = std::fs::read(path)?;
+        Self::from_bytes(&bytes)
+    }
+}
+
+// written out by hand so reordering `PlayerState` cant change what saved ghosts mean,
+// new states get the next free number
+fn state_to_byte(state: PlayerState) -> u8 {
+    match state {
+        PlayerState::Grounded => 0,
+        PlayerState::Jumping => 1,
+        PlayerState::Falling => 2,
+        PlayerState::WallSliding => 3,
+        PlayerState::Dashing => 4,
+        PlayerState::Dead => 5,
+    }
+}
+
+fn state_from_byte(byte: u8) -> Option<PlayerState> {
+    match byte {
+        0 => Some(PlayerState::Grounded),
+        1 => Some(PlayerState::Jumping),
+        2 => Some(PlayerState::Falling),
+        3 => Some(PlayerState::WallSliding),
+        4 => Some(PlayerState::Dashing),
+        5 => Some(PlayerState::Dead),
+        _ => None,
+    }
+}
+
+/// plays a saved ghost back next to the live player
+#[derive(Clone, Debug)]
+pub struct GhostPlayback {
+    ghost: Ghost,
+    // the ghost frame the player was last closest to
+    last_match: usize,
+}
+
+impl GhostPlayback {
+    pub fn new(ghost: Ghost) -> Self {
+        Self {
+            ghost,
+            last_match: 0,
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.last_match = 0;
+    }
+
+    /// where the ghost is after `tick` ticks, blended like the live player. stays on its
+    /// last frame once the run is over
+    pub fn pos_at(&self, tick: u64, alpha: f32) -> Option<Vec2<f32>> {
+        let frames = self.ghost.get_frames();
+        let last = frames.len().checked_sub(1)?;
+
+        let current = (tick as usize).saturating_sub(1).min(last);
+        let previous = (tick as usize).saturating_sub(2).min(last);
+        let (a, b) = (frames[previous].pos, frames[current].pos);
+
+        Some(vec2!(lerp(a.x, b.x, alpha), lerp(a.y, b.y, alpha)))
+    }
+
+    /// seconds the live player is behind the ghost (positive) or ahead of it (negative),
+    /// found by comparing the current time against when the ghost was closest to `pos`
+    pub fn split(&mut self, pos: Vec2<f32>, tick: u64) -> Option<f32> {
+        let frames = self.ghost.get_frames();
+        if frames.is_empty() {
+            return None;
+        }
+
+        // only look near the last match so a path that doubles back doesnt make the split jump
+        let start = self.last_match.saturating_sub(SPLIT_SEARCH_TICKS);
+        let end = (self.last_match + SPLIT_SEARCH_TICKS).min(frames.len());
+
+        let distance = |f: &GhostFrame| {
+            let d = f.pos - pos;
+            d.x * d.x + d.y * d.y
+        };
+
+        self.last_match = (start..end)
+            .min_by(|a, b| distance(&frames[*a]).total_cmp(&distance(&frames[*b])))
+            .unwrap_or(self.last_match);
+
+        let ghost_tick = self.last_match as i64 + 1;
+        Some((tick as i64 - ghost_tick) as f32 * FIXED_DT)
+    }
+}
+
+#[derive(Debug)]
+pub enum GhostError {
+    Io(std::io::Error),
+    BadMagic,
+    UnsupportedVersion(u16),
+    Truncated,
+    BadState(u8),
+}
+
+impl Display for GhostError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "could not read ghost: {}", e),
+            Self::BadMagic => write!(f, "not a ghost file"),
+            Self::UnsupportedVersion(v) => write!(f, "ghost version {} is not supported", v),
+            Self::Truncated => write!(f, "ghost file is cut short"),
+            Self::BadState(b) => write!(f, "unknown player state {}", b),
+        }
+    }
+}
+
+impl std::error::Error for GhostError {}
+
+impl From<std::io::Error> for GhostError {
+    fn from(value: std::io::Error) -> Self {
+        Self::Io(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::{Action, ActionSet};
+    use crate::movement::MovementProfile;
+    use crate::sim::Simulation;
+
+    use level_editor::level::Platform;
+
+    fn test_level() -> Level {
+        Level::headless(vec![
+            Platform::new(vec2!(10.0, 200.0), vec2!(300.0, 100.0)),
+            Platform::new(vec2!(0.0, 600.0), vec2!(600.0, 50.0)),
+        ])
+    }
+
+    fn run(ticks: usize, held: ActionSet) -> Ghost {
+        let mut sim = Simulation::new(test_level(), MovementProfile::default());
+        let mut ghost = Ghost::new(sim.get_level());
+
+        for _ in 0..ticks {
+            sim.step(held);
+            ghost.record(sim.get_character());
+        }
+
+        ghost
+    }
+
+    #[test]
+    fn round_trips_through_bytes() {
+        let ghost = run(300, ActionSet::empty().with(Action::MoveRight));
+        let bytes = ghost.to_bytes();
+
+        assert_eq!(bytes.len(), PREAMBLE_LEN + 300 * FRAME_LEN);
+        assert_eq!(Ghost::from_bytes(&bytes).unwrap(), ghost);
+
+        let mut bad_state = bytes.clone();
+        bad_state[PREAMBLE_LEN + 8] = 200;
+        assert!(matches!(Ghost::from_bytes(&bad_state), Err(GhostError::BadState(200))));
+        assert!(matches!(Ghost::from_bytes(&bytes[..bytes.len() - 1]), Err(GhostError::Truncated)));
+
+        // version 1 ghosts were timed from spawn so they cant be compared with runs any more
+        let mut old = bytes.clone();
+        old[4..6].copy_from_slice(&1u16.to_le_bytes());
+        assert!(matches!(Ghost::from_bytes(&old), Err(GhostError::UnsupportedVersion(1))));
+    }
+
+    #[test]
+    fn state_bytes_are_fixed() {
+        for state in PlayerState::ALL {
+            assert_eq!(state_from_byte(state_to_byte(state)), Some(state));
+        }
+
+        // these are in saved ghosts, changing one breaks every file out there
+        assert_eq!(state_to_byte(PlayerState::Grounded), 0);
+        assert_eq!(state_to_byte(PlayerState::Dead), 5);
+        assert_eq!(state_from_byte(6), None);
+    }
+
+    #[test]
+    fn only_quicker_runs_replace_the_best() {
+        let dir = std::env::temp_dir().join("speed_game_ghost_test");
+        let _ = std::fs::remove_dir_all(&dir);
+
+        assert!(run(300, ActionSet::empty()).save_if_best(&dir).unwrap());
+        assert!(!run(400, ActionSet::empty()).save_if_best(&dir).unwrap());
+        assert!(run(200, ActionSet::empty()).save_if_best(&dir).unwrap());
+
+        let best = Ghost::load_best(&dir, &test_level()).unwrap();
+        assert_eq!(best.len(), 200);
+    }
+
+    #[test]
+    fn split_is_zero_when_matching_the_ghost() {
+        let right = ActionSet::empty().with(Action::MoveRight);
+        let ghost = run(600, right);
+        let mut playback = GhostPlayback::new(ghost.clone());
+
+        for (i, frame) in ghost.get_frames().iter().enumerate() {
+            assert_eq!(playback.split(frame.pos, i as u64 + 1), Some(0.0), "tick {}", i + 1);
+        }
+    }
+
+    #[test]
+    fn split_shows_time_lost() {
+        let right = ActionSet::empty().with(Action::MoveRight);
+        let ghost = run(600, right);
+        let mut playback = GhostPlayback::new(ghost.clone());
+
+        // reaching where the ghost was at tick 500 one second late
+        let pos = ghost.get_frames()[499].pos;
+        for tick in 1..=620 {
+            playback.split(pos, tick);
+        }
+
+        let split = playback.split(pos, 620).unwrap();
+        assert!((split - 1.0).abs() < 0.001, "{}", split);
+    }
+}
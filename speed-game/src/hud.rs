@@ -0,0 +1,305 @@
+use bottomless_pit::colour::Colour;
+use bottomless_pit::engine_handle::Engine;
+use bottomless_pit::material::{Material, MaterialBuilder};
+use bottomless_pit::render::RenderInformation;
+use bottomless_pit::text::TextMaterial;
+use bottomless_pit::vec2;
+use bottomless_pit::vectors::Vec2;
+
+use level_editor::level::{LevelMeta, Medal};
+
+use utils::keys::key_name;
+
+use crate::input::{Action, Bindings};
+use crate::timer::{format_secs, format_time, SplitResult};
+
+// gap between the text and the edge of the screen
+const MARGIN: f32 = 8.0;
+// how long a checkpoint split stays up in seconds
+const SPLIT_SHOW_TIME: f32 = 3.0;
+// how long the level name stays up after loading a level in seconds
+const TITLE_SHOW_TIME: f32 = 3.0;
+
+/// things shown on top of the game while playing
+pub struct Hud {
+    timer_text: TextLine,
+    split_text: TextLine,
+    ghost_text: TextLine,
+    // shown in the middle of the screen once the level is finished
+    results: [TextLine; 3],
+    // the level name and what to beat, shown near the top when a level is loaded
+    title: [TextLine; 2],
+    // seconds left before the last checkpoint split is hidden again
+    split_time_left: f32,
+    title_time_left: f32,
+    screen_size: Vec2<f32>,
+}
+
+impl Hud {
+    pub fn new(engine: &mut Engine, screen_size: Vec2<f32>) -> Self {
+        Self {
+            timer_text: TextLine::new(engine),
+            split_text: TextLine::new(engine),
+            ghost_text: TextLine::new(engine),
+            results: [TextLine::new(engine), TextLine::new(engine), TextLine::new(engine)],
+            title: [TextLine::new(engine), TextLine::new(engine)],
+            split_time_left: 0.0,
+            title_time_left: 0.0,
+            screen_size,
+        }
+    }
+
+    pub fn set_timer(&mut self, ticks: u64) {
+        self.timer_text.set(format_time(ticks), Colour::WHITE);
+    }
+
+    /// shows how a checkpoint went for a few seconds
+    pub fn show_split(&mut self, split: SplitResult) {
+        let (text, colour) = match split {
+            SplitResult::Gold(d) => (format_delta(d), Colour::from_rgba(255.0, 200.0, 40.0, 1.0)),
+            SplitResult::Ahead(d) => (format_delta(d), delta_colour(d)),
+            SplitResult::Behind(d) => (format_delta(d), delta_colour(d)),
+            SplitResult::New => (String::from("split"), Colour::WHITE),
+        };
+
+        self.split_text.set(text, colour);
+        self.split_time_left = SPLIT_SHOW_TIME;
+    }
+
+    /// `delta` is how many seconds behind the ghost the player is, None hides it
+    pub fn set_ghost_delta(&mut self, delta: Option<f32>) {
+        match delta {
+            Some(delta) => self.ghost_text.set(format!("ghost {}", format_delta(delta)), delta_colour(delta)),
+            None => self.ghost_text.clear(),
+        }
+    }
+
+    /// the title card for a level that was just loaded, goes away on its own
+    pub fn show_title(&mut self, meta: &LevelMeta) {
+        let name = if meta.name.is_empty() { "untitled level" } else { &meta.name };
+        self.title[0].set(name.to_string(), Colour::WHITE);
+
+        let mut details = Vec::new();
+        if !meta.author.is_empty() {
+            details.push(format!("by {}", meta.author));
+        }
+        if let Some(difficulty) = meta.difficulty {
+            details.push(difficulty.name().to_string());
+        }
+        if let Some(par) = meta.get_par_time() {
+            details.push(format!("par {}", format_secs(par)));
+        }
+        self.title[1].set(details.join("  "), Colour::WHITE);
+
+        self.title_time_left = TITLE_SHOW_TIME;
+    }
+
+    /// the end of level screen, `best` is the personal best before this run
+    pub fn show_results(&mut self, ticks: u64, best: Option<u64>, deaths: u32, medal: Option<Medal>, has_next: bool, bindings: &Bindings) {
+        let deaths = match deaths {
+            0 => String::from("deathless"),
+            1 => String::from("1 death"),
+            n => format!("{} deaths", n),
+        };
+        let medal = medal.map(|m| format!(", {} medal", m.name())).unwrap_or_default();
+        self.results[0].set(format!("finished in {}, {}{}", format_time(ticks), deaths, medal), Colour::WHITE);
+
+        let (text, colour) = match best {
+            Some(best) if ticks < best => (format!("new best! was {}", format_time(best)), Colour::from_rgba(255.0, 200.0, 40.0, 1.0)),
+            Some(best) => (format!("best {}", format_time(best)), Colour::WHITE),
+            None => (String::from("first clear"), Colour::WHITE),
+        };
+        self.results[1].set(text, colour);
+
+        self.results[2].set(results_hint(bindings, has_next), Colour::WHITE);
+    }
+
+    pub fn showing_results(&self) -> bool {
+        !self.results[0].is_empty()
+    }
+
+    pub fn reset(&mut self) {
+        self.split_text.clear();
+        self.split_time_left = 0.0;
+        for line in self.results.iter_mut().chain(&mut self.title) {
+            line.clear();
+        }
+        self.title_time_left = 0.0;
+    }
+
+    pub fn prepare(&mut self, engine: &mut Engine, dt: f32) {
+        self.split_time_left -= dt;
+        if self.split_time_left <= 0.0 {
+            self.split_text.clear();
+        }
+
+        self.title_time_left -= dt;
+        if self.title_time_left <= 0.0 {
+            for line in &mut self.title {
+                line.clear();
+            }
+        }
+
+        self.timer_text.prepare(engine);
+        self.split_text.prepare(engine);
+        self.ghost_text.prepare(engine);
+        for line in self.results.iter_mut().chain(&mut self.title) {
+            line.prepare(engine);
+        }
+    }
+
+    pub fn draw<'p, 'o>(&'o mut self, renderer: &mut RenderInformation<'p, 'o>) where 'o: 'p {
+        renderer.reset_camera();
+        let showing_results = self.showing_results();
+
+        // stacked down the right side, empty lines take no space
+        let mut y = MARGIN;
+        for line in [&mut self.timer_text, &mut self.split_text, &mut self.ghost_text] {
+            if line.is_empty() {
+                continue;
+            }
+
+            let x = self.screen_size.x - line.width() - MARGIN;
+            let height = line.height();
+            line.draw(renderer, x, y);
+            y += height + MARGIN / 2.0;
+        }
+
+        // centered a quarter of the way down so it doesnt cover the player
+        let mut y = self.screen_size.y / 4.0;
+        for line in &mut self.title {
+            if line.is_empty() {
+                continue;
+            }
+
+            let x = (self.screen_size.x - line.width()) / 2.0;
+            let height = line.height();
+            line.draw(renderer, x, y);
+            y += height + MARGIN / 2.0;
+        }
+
+        if !showing_results {
+            return;
+        }
+
+        // centered as a block in the middle of the screen
+        let total_height: f32 = self.results.iter().map(|l| l.height() + MARGIN).sum::<f32>() - MARGIN;
+        let mut y = (self.screen_size.y - total_height) / 2.0;
+        for line in &mut self.results {
+            let x = (self.screen_size.x - line.width()) / 2.0;
+            let height = line.height();
+            line.draw(renderer, x, y);
+            y += height + MARGIN;
+        }
+    }
+}
+
+/// `+1.25` when behind and `-0.40` when ahead
+pub fn format_delta(seconds: f32) -> String {
+    let sign = if seconds < 0.0 { '-' } else { '+' };
+    format!("{}{:.2}", sign, seconds.abs())
+}
+
+/// what to press on the results screen, uses whatever the keys are bound to and leaves
+/// out anything thats not bound at all
+fn results_hint(bindings: &Bindings, has_next: bool) -> String {
+    let hint = |action, what| {
+        let keys = bindings.keys_for(action);
+        (!keys.is_empty()).then(|| {
+            let keys = keys.iter().map(|k| key_name(*k)).collect::<Vec<_>>().join(" or ");
+            format!("{} {}", keys, what)
+        })
+    };
+
+    let mut hints = vec![hint(Action::Restart, "to restart")];
+    if has_next {
+        hints.push(hint(Action::NextLevel, "for the next level"));
+    }
+
+    hints.into_iter().flatten().collect::<Vec<_>>().join("  ")
+}
+
+fn delta_colour(seconds: f32) -> Colour {
+    if seconds <= 0.0 {
+        Colour::from_rgba(120.0, 230.0, 120.0, 1.0)
+    } else {
+        Colour::from_rgba(240.0, 90.0, 90.0, 1.0)
+    }
+}
+
+/// a single line of text on a dark background
+struct TextLine {
+    text_mat: TextMaterial,
+    material: Material,
+    text: String,
+    colour: Colour,
+}
+
+impl TextLine {
+    fn new(engine: &mut Engine) -> Self {
+        Self {
+            text_mat: TextMaterial::new("", Colour::WHITE, 16.0, 18.0, engine),
+            material: MaterialBuilder::new().build(engine),
+            text: String::new(),
+            colour: Colour::WHITE,
+        }
+    }
+
+    fn set(&mut self, text: String, colour: Colour) {
+        self.text = text;
+        self.colour = colour;
+    }
+
+    fn clear(&mut self) {
+        self.text.clear();
+    }
+
+    fn is_empty(&self) -> bool {
+        self.text.is_empty()
+    }
+
+    fn width(&self) -> f32 {
+        self.text_mat.get_measurements().x as f32
+    }
+
+    fn height(&self) -> f32 {
+        self.text_mat.get_measurements().y as f32
+    }
+
+    fn prepare(&mut self, engine: &mut Engine) {
+        if !self.text.is_empty() {
+            self.text_mat.set_text(&self.text, self.colour, engine);
+            self.text_mat.prepare(engine);
+        }
+    }
+
+    fn draw<'p, 'o>(&'o mut self, renderer: &mut RenderInformation<'p, 'o>, x: f32, y: f32) where 'o: 'p {
+        if self.text.is_empty() {
+            return;
+        }
+
+        let size = self.text_mat.get_measurements();
+        self.material.add_rectangle(vec2!(x, y), vec2!(size.x as f32, size.y as f32), Colour::BLACK, renderer);
+        self.text_mat.add_instance(vec2!(x, y), Colour::WHITE, renderer);
+
+        self.material.draw(renderer);
+        self.text_mat.draw(renderer);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bottomless_pit::input::Key;
+
+    #[test]
+    fn results_hint_uses_the_bound_keys() {
+        let mut bindings = Bindings::default();
+        assert_eq!(results_hint(&bindings, true), "R to restart  N for the next level");
+        assert_eq!(results_hint(&bindings, false), "R to restart");
+
+        bindings.bind(Action::Restart, vec![Key::Backspace, Key::F1]);
+        bindings.bind(Action::NextLevel, vec![]);
+        assert_eq!(results_hint(&bindings, true), "Backspace or F1 to restart");
+    }
+}
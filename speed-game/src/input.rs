@@ -0,0 +1,455 @@
+use std::fmt::Display;
+use std::path::Path;
+
+use bottomless_pit::engine_handle::Engine;
+use bottomless_pit::input::Key;
+
+use utils::config::{parse_entries, ConfigError};
+use utils::keys::{key_from_name, key_name};
+
+/// everything the player can ask the game to do, independent of which key does it
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Action {
+    MoveLeft,
+    MoveRight,
+    MoveUp,
+    MoveDown,
+    Jump,
+    Dash,
+    ToggleDebug,
+    SaveReplay,
+    Restart,
+    NextLevel,
+}
+
+impl Action {
+    pub const ALL: [Self; 10] = [
+        Self::MoveLeft,
+        Self::MoveRight,
+        Self::MoveUp,
+        Self::MoveDown,
+        Self::Jump,
+        Self::Dash,
+        Self::ToggleDebug,
+        Self::SaveReplay,
+        Self::Restart,
+        Self::NextLevel,
+    ];
+
+    /// the name used in the controls file
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::MoveLeft => "move_left",
+            Self::MoveRight => "move_right",
+            Self::MoveUp => "move_up",
+            Self::MoveDown => "move_down",
+            Self::Jump => "jump",
+            Self::Dash => "dash",
+            Self::ToggleDebug => "toggle_debug",
+            Self::SaveReplay => "save_replay",
+            Self::Restart => "restart",
+            Self::NextLevel => "next_level",
+        }
+    }
+
+    /// moves the player, as opposed to things like the debug overlay or restarting
+    pub fn is_gameplay(&self) -> bool {
+        matches!(self, Self::MoveLeft | Self::MoveRight | Self::MoveUp | Self::MoveDown | Self::Jump | Self::Dash)
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|a| a.name() == name)
+    }
+
+    fn bit(&self) -> u16 {
+        1 << (*self as u16)
+    }
+}
+
+/// a set of actions packed into bits, cheap to copy around and store per tick
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct ActionSet(u16);
+
+impl ActionSet {
+    pub fn empty() -> Self {
+        Self(0)
+    }
+
+    pub fn from_bits(bits: u16) -> Self {
+        Self(bits)
+    }
+
+    pub fn bits(&self) -> u16 {
+        self.0
+    }
+
+    pub fn contains(&self, action: Action) -> bool {
+        self.0 & action.bit() != 0
+    }
+
+    pub fn insert(&mut self, action: Action) {
+        self.0 |= action.bit();
+    }
+
+    pub fn remove(&mut self, action: Action) {
+        self.0 &= !action.bit();
+    }
+
+    pub fn has_gameplay(&self) -> bool {
+        Action::ALL.into_iter().any(|a| a.is_gameplay() && self.contains(a))
+    }
+
+    pub fn with(mut self, action: Action) -> Self {
+        self.insert(action);
+        self
+    }
+}
+
+impl FromIterator<Action> for ActionSet {
+    fn from_iter<T: IntoIterator<Item = Action>>(iter: T) -> Self {
+        let mut set = Self::empty();
+        for action in iter {
+            set.insert(action);
+        }
+        set
+    }
+}
+
+/// what is held this tick and last tick so presses and releases can be picked out
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct InputState {
+    held: ActionSet,
+    previous: ActionSet,
+}
+
+impl InputState {
+    /// moves on to the next tick
+    pub fn advance(&mut self, held: ActionSet) {
+        self.previous = self.held;
+        self.held = held;
+    }
+
+    pub fn held(&self) -> ActionSet {
+        self.held
+    }
+
+    pub fn is_down(&self, action: Action) -> bool {
+        self.held.contains(action)
+    }
+
+    /// only true on the tick the action started
+    pub fn is_pressed(&self, action: Action) -> bool {
+        self.held.contains(action) && !self.previous.contains(action)
+    }
+
+    /// only true on the tick the action stopped
+    pub fn is_released(&self, action: Action) -> bool {
+        !self.held.contains(action) && self.previous.contains(action)
+    }
+
+    /// -1 for left, 1 for right and 0 for both or neither
+    pub fn horizontal(&self) -> f32 {
+        Self::axis(self.is_down(Action::MoveLeft), self.is_down(Action::MoveRight))
+    }
+
+    /// -1 for up, 1 for down and 0 for both or neither
+    pub fn vertical(&self) -> f32 {
+        Self::axis(self.is_down(Action::MoveUp), self.is_down(Action::MoveDown))
+    }
+
+    fn axis(negative: bool, positive: bool) -> f32 {
+        let mut value = 0.0;
+        if positive {
+            value += 1.0;
+        }
+        if negative {
+            value -= 1.0;
+        }
+        value
+    }
+}
+
+/// where the held actions for each tick come from
+pub trait InputSource {
+    /// called once per rendered frame before any ticks run, sources that dont
+    /// read the keyboard can ignore it
+    fn begin_frame(&mut self, _engine: &Engine) {}
+
+    /// the actions held during the next tick
+    fn next_tick(&mut self) -> ActionSet;
+
+    /// called when the run restarts, sources that play back a fixed run go back to its start
+    fn rewind(&mut self) {}
+}
+
+/// reads the keyboard through a set of bindings
+pub struct KeyboardInput {
+    bindings: Bindings,
+    held: ActionSet,
+}
+
+impl KeyboardInput {
+    pub fn new(bindings: Bindings) -> Self {
+        Self {
+            bindings,
+            held: ActionSet::empty(),
+        }
+    }
+}
+
+impl InputSource for KeyboardInput {
+    fn begin_frame(&mut self, engine: &Engine) {
+        self.held = self.bindings.held_actions(engine);
+    }
+
+    fn next_tick(&mut self) -> ActionSet {
+        self.held
+    }
+}
+
+/// plays back a fixed list of ticks, then holds nothing once it runs out
+#[derive(Clone, Debug, Default)]
+pub struct ScriptedInput {
+    ticks: Vec<ActionSet>,
+    position: usize,
+}
+
+impl ScriptedInput {
+    pub fn new(ticks: Vec<ActionSet>) -> Self {
+        Self {
+            ticks,
+            position: 0,
+        }
+    }
+
+    /// holds `actions` for the next `ticks` ticks
+    pub fn hold(mut self, actions: ActionSet, ticks: usize) -> Self {
+        self.ticks.extend(std::iter::repeat_n(actions, ticks));
+        self
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.position >= self.ticks.len()
+    }
+
+    pub fn len(&self) -> usize {
+        self.ticks.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ticks.is_empty()
+    }
+}
+
+impl InputSource for ScriptedInput {
+    fn next_tick(&mut self) -> ActionSet {
+        let actions = self.ticks.get(self.position).copied().unwrap_or_default();
+        self.position += 1;
+        actions
+    }
+}
+
+/// which keys trigger which action, any number of keys can share an action
+#[derive(Clone, Debug, PartialEq)]
+pub struct Bindings {
+    keys: Vec<(Action, Vec<Key>)>,
+}
+
+impl Bindings {
+    /// an action with no binding in the text keeps its default keys, bind it to
+    /// nothing with an empty value
+    pub fn parse(text: &str) -> Result<Self, ConfigError> {
+        let mut bindings = Self::default();
+
+        for entry in parse_entries(text)? {
+            let action = Action::from_name(entry.key).ok_or_else(|| entry.unknown_key())?;
+
+            let mut keys = Vec::new();
+            for name in entry.value.split(',').map(str::trim).filter(|n| !n.is_empty()) {
+                let key = key_from_name(name)
+                    .ok_or_else(|| ConfigError::new(entry.line, format!("`{}` is not a key that can be bound", name)))?;
+                keys.push(key);
+            }
+
+            bindings.bind(action, keys);
+        }
+
+        Ok(bindings)
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, BindingsError> {
+        let text = std::fs::read_to_string(path)?;
+        Ok(Self::parse(&text)?)
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> std::io::Result<()> {
+        std::fs::write(path, self.to_string())
+    }
+
+    /// replaces every key on `action`
+    pub fn bind(&mut self, action: Action, keys: Vec<Key>) {
+        match self.keys.iter_mut().find(|(a, _)| *a == action) {
+            Some((_, bound)) => *bound = keys,
+            None => self.keys.push((action, keys)),
+        }
+    }
+
+    pub fn keys_for(&self, action: Action) -> &[Key] {
+        self.keys
+            .iter()
+            .find(|(a, _)| *a == action)
+            .map(|(_, keys)| keys.as_slice())
+            .unwrap_or(&[])
+    }
+
+    pub fn held_actions(&self, engine: &Engine) -> ActionSet {
+        self.keys
+            .iter()
+            .filter(|(_, keys)| keys.iter().any(|k| engine.is_key_down(*k)))
+            .map(|(action, _)| *action)
+            .collect()
+    }
+
+    /// true on the frame any key bound to `action` went down. for things outside
+    /// the fixed tick like menus and the debug overlay
+    pub fn is_pressed(&self, engine: &Engine, action: Action) -> bool {
+        self.keys_for(action).iter().any(|k| engine.is_key_pressed(*k))
+    }
+}
+
+impl Default for Bindings {
+    fn default() -> Self {
+        Self {
+            keys: vec![
+                (Action::MoveLeft, vec![Key::A]),
+                (Action::MoveRight, vec![Key::D]),
+                (Action::MoveUp, vec![Key::W]),
+                (Action::MoveDown, vec![Key::S]),
+                (Action::Jump, vec![Key::Space]),
+                (Action::Dash, vec![Key::J]),
+                (Action::ToggleDebug, vec![Key::F3, Key::P]),
+                (Action::SaveReplay, vec![Key::F5]),
+                (Action::Restart, vec![Key::R]),
+                (Action::NextLevel, vec![Key::N]),
+            ],
+        }
+    }
+}
+
+/// writes the bindings back out in the same format `parse` reads
+impl Display for Bindings {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for action in Action::ALL {
+            let keys = self.keys_for(action)
+                .iter()
+                .map(|k| key_name(*k))
+                .collect::<Vec<_>>()
+                .join(", ");
+
+            writeln!(f, "{} = {}", action.name(), keys)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+pub enum BindingsError {
+    Io(std::io::Error),
+    Parse(ConfigError),
+}
+
+impl Display for BindingsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "could not read controls: {}", e),
+            Self::Parse(e) => write!(f, "bad controls file: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for BindingsError {}
+
+impl From<std::io::Error> for BindingsError {
+    fn from(value: std::io::Error) -> Self {
+        Self::Io(value)
+    }
+}
+
+impl From<ConfigError> for BindingsError {
+    fn from(value: ConfigError) -> Self {
+        Self::Parse(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn presses_and_releases_last_one_tick() {
+        let mut input = InputState::default();
+        let jump = ActionSet::empty().with(Action::Jump);
+
+        input.advance(jump);
+        assert!(input.is_pressed(Action::Jump));
+        input.advance(jump);
+        assert!(!input.is_pressed(Action::Jump));
+        assert!(input.is_down(Action::Jump));
+
+        input.advance(ActionSet::empty());
+        assert!(input.is_released(Action::Jump));
+        input.advance(ActionSet::empty());
+        assert!(!input.is_released(Action::Jump));
+    }
+
+    #[test]
+    fn opposite_directions_cancel() {
+        let mut input = InputState::default();
+        input.advance([Action::MoveLeft, Action::MoveRight, Action::MoveDown].into_iter().collect());
+
+        assert_eq!(input.horizontal(), 0.0);
+        assert_eq!(input.vertical(), 1.0);
+    }
+
+    #[test]
+    fn scripted_input_runs_out_to_nothing() {
+        let right = ActionSet::empty().with(Action::MoveRight);
+        let mut script = ScriptedInput::default().hold(right, 2);
+
+        assert_eq!(script.next_tick(), right);
+        assert_eq!(script.next_tick(), right);
+        assert!(script.is_finished());
+        assert_eq!(script.next_tick(), ActionSet::empty());
+    }
+
+    #[test]
+    fn bindings_round_trip_through_text() {
+        let mut bindings = Bindings::default();
+        bindings.bind(Action::Jump, vec![Key::K, Key::Space]);
+        bindings.bind(Action::Dash, vec![]);
+
+        let parsed = Bindings::parse(&bindings.to_string()).unwrap();
+        assert_eq!(parsed.keys_for(Action::Jump), &[Key::K, Key::Space]);
+        assert!(parsed.keys_for(Action::Dash).is_empty());
+        assert_eq!(parsed.keys_for(Action::MoveLeft), &[Key::A]);
+    }
+
+    #[test]
+    fn arrows_digits_and_modifiers_can_be_bound() {
+        let bindings = Bindings::parse("move_left = Left, a\ndash = LeftShift\nrestart = Key1").unwrap();
+
+        assert_eq!(bindings.keys_for(Action::MoveLeft), &[Key::Left, Key::A]);
+        assert_eq!(bindings.keys_for(Action::Dash), &[Key::LeftShift]);
+        assert_eq!(bindings.keys_for(Action::Restart), &[Key::Key1]);
+    }
+
+    #[test]
+    fn bad_bindings_report_the_line() {
+        let err = Bindings::parse("jump = Space\ndash = Banana").unwrap_err();
+        assert_eq!(err.line, 2);
+
+        let err = Bindings::parse("fly = Space").unwrap_err();
+        assert_eq!(err.line, 1);
+    }
+}
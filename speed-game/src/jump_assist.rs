@@ -0,0 +1,205 @@
+/// how forgiving jumping is about timing, both in seconds
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct JumpAssistConfig {
+    /// how long after running off a ledge a jump still counts
+    pub coyote_time: f32,
+    /// how long a jump pressed before landing is remembered
+    pub jump_buffer: f32,
+}
+
+impl Default for JumpAssistConfig {
+    fn default() -> Self {
+        Self {
+            coyote_time: 0.1,
+            jump_buffer: 0.1,
+        }
+    }
+}
+
+/// tracks the coyote and jump buffer windows, ticked once per simulation step
+#[derive(Clone, Copy, Debug)]
+pub struct JumpAssist {
+    config: JumpAssistConfig,
+    coyote_timer: f32,
+    buffer_timer: f32,
+}
+
+impl JumpAssist {
+    pub fn new(config: JumpAssistConfig) -> Self {
+        Self {
+            config,
+            coyote_timer: 0.0,
+            buffer_timer: 0.0,
+        }
+    }
+
+    pub fn set_config(&mut self, config: JumpAssistConfig) {
+        self.config = config;
+        self.coyote_timer = self.coyote_timer.min(config.coyote_time);
+        self.buffer_timer = self.buffer_timer.min(config.jump_buffer);
+    }
+
+    /// `grounded` is whether the player stood on something at the start of this tick
+    /// and `jump_pressed` is true only on the tick the button went down
+    pub fn tick(&mut self, dt: f32, grounded: bool, jump_pressed: bool) {
+        if grounded {
+            self.coyote_timer = self.config.coyote_time;
+        } else {
+            self.coyote_timer = (self.coyote_timer - dt).max(0.0);
+        }
+
+        if jump_pressed {
+            self.buffer_timer = self.config.jump_buffer;
+        } else {
+            self.buffer_timer = (self.buffer_timer - dt).max(0.0);
+        }
+
+        // a zero length window still has to allow jumping on the exact tick
+        if grounded && self.config.coyote_time == 0.0 {
+            self.coyote_timer = f32::MIN_POSITIVE;
+        }
+        if jump_pressed && self.config.jump_buffer == 0.0 {
+            self.buffer_timer = f32::MIN_POSITIVE;
+        }
+    }
+
+    /// there is a jump waiting to happen
+    pub fn is_buffered(&self) -> bool {
+        self.buffer_timer > 0.0
+    }
+
+    /// a buffered jump and either solid ground or a recent ledge
+    pub fn can_jump(&self) -> bool {
+        self.is_buffered() && self.coyote_timer > 0.0
+    }
+
+    /// call when a jump actually happens so it cant fire twice
+    pub fn consume(&mut self) {
+        self.coyote_timer = 0.0;
+        self.buffer_timer = 0.0;
+    }
+}
+
+impl Default for JumpAssist {
+    fn default() -> Self {
+        Self::new(JumpAssistConfig::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DT: f32 = 1.0 / 120.0;
+
+    fn run(assist: &mut JumpAssist, ticks: usize, grounded: bool) {
+        for _ in 0..ticks {
+            assist.tick(DT, grounded, false);
+        }
+    }
+
+    #[test]
+    fn jump_on_the_ground() {
+        let mut assist = JumpAssist::default();
+        run(&mut assist, 5, true);
+
+        assist.tick(DT, true, true);
+        assert!(assist.can_jump());
+    }
+
+    #[test]
+    fn no_jump_without_press() {
+        let mut assist = JumpAssist::default();
+        run(&mut assist, 5, true);
+
+        assert!(!assist.can_jump());
+    }
+
+    #[test]
+    fn late_jump_inside_coyote_time() {
+        let mut assist = JumpAssist::default();
+        run(&mut assist, 5, true);
+        // ran off the ledge a few ticks ago
+        run(&mut assist, 5, false);
+
+        assist.tick(DT, false, true);
+        assert!(assist.can_jump());
+    }
+
+    #[test]
+    fn late_jump_after_coyote_time() {
+        let mut assist = JumpAssist::default();
+        run(&mut assist, 5, true);
+        run(&mut assist, 13, false);
+
+        assist.tick(DT, false, true);
+        assert!(!assist.can_jump());
+    }
+
+    #[test]
+    fn early_jump_is_buffered_until_landing() {
+        let mut assist = JumpAssist::default();
+        run(&mut assist, 30, false);
+
+        assist.tick(DT, false, true);
+        assert!(!assist.can_jump());
+        run(&mut assist, 5, false);
+
+        assist.tick(DT, true, false);
+        assert!(assist.can_jump());
+    }
+
+    #[test]
+    fn buffer_runs_out() {
+        let mut assist = JumpAssist::default();
+        run(&mut assist, 30, false);
+
+        assist.tick(DT, false, true);
+        run(&mut assist, 13, false);
+
+        assist.tick(DT, true, false);
+        assert!(!assist.can_jump());
+    }
+
+    #[test]
+    fn consumed_jump_cant_fire_again_in_the_air() {
+        let mut assist = JumpAssist::default();
+        assist.tick(DT, true, true);
+        assert!(assist.can_jump());
+        assist.consume();
+
+        // a second press right after leaving the ground is not a double jump
+        assist.tick(DT, false, true);
+        assert!(!assist.can_jump());
+        assert!(assist.is_buffered());
+    }
+
+    #[test]
+    fn zero_windows_are_frame_strict() {
+        let mut assist = JumpAssist::new(JumpAssistConfig {
+            coyote_time: 0.0,
+            jump_buffer: 0.0,
+        });
+
+        assist.tick(DT, true, true);
+        assert!(assist.can_jump());
+
+        assist.tick(DT, true, false);
+        assert!(!assist.can_jump());
+
+        assist.tick(DT, false, true);
+        assert!(!assist.can_jump());
+    }
+
+    #[test]
+    fn shrinking_config_clamps_running_windows() {
+        let mut assist = JumpAssist::default();
+        assist.tick(DT, true, true);
+
+        assist.set_config(JumpAssistConfig {
+            coyote_time: 0.0,
+            jump_buffer: 0.0,
+        });
+        assert!(!assist.can_jump());
+    }
+}
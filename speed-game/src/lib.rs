@@ -1,2 +1,14 @@
 pub mod character;
-mod debug;
+pub mod debug;
+pub mod effects;
+pub mod ghost;
+pub mod hud;
+pub mod input;
+pub mod jump_assist;
+pub mod movement;
+pub mod replay;
+pub mod sim;
+pub mod sprite;
+pub mod state;
+pub mod timer;
+pub mod timestep;
\ No newline at end of file
@@ -1,56 +1,312 @@
-mod character;
-mod debug;
+use level_editor::entity::{Entity, EntityKind};
+use level_editor::level::{Difficulty, Level, Platform, TEXT_EXTENSION};
+use speed_game::debug::DebugText;
+use speed_game::effects::{CheckpointEffects, DeathEffect};
+use speed_game::ghost::{Ghost, GhostPlayback};
+use speed_game::hud::Hud;
+use speed_game::input::{Action, Bindings, BindingsError, InputSource, KeyboardInput};
+use speed_game::movement::{MovementProfile, ProfileWatcher};
+use speed_game::replay::{Replay, ReplayInput};
+use speed_game::sim::{SimEvent, Simulation};
+use speed_game::sprite::PlayerSprite;
+use speed_game::timer::{ticks_to_secs, PersonalBest, SpeedrunTimer, TimerState};
+use speed_game::timestep::FixedTimestep;
 
-use character::Character;
-use level_editor::level::{Level, Platform};
-use debug::DebugText;
+use std::path::{Path, PathBuf};
 
 use bottomless_pit::camera::Camera;
-use bottomless_pit::material::{Material, MaterialBuilder};
+use bottomless_pit::material::MaterialBuilder;
 use bottomless_pit::{vec2, Game};
-use bottomless_pit::vectors::Vec2;
 use bottomless_pit::engine_handle::{Engine, EngineBuilder};
 use bottomless_pit::render::RenderInformation;
 
+const MOVEMENT_PROFILE_PATH: &str = "speed-game/assets/movement.cfg";
+const CONTROLS_PATH: &str = "speed-game/assets/controls.cfg";
+const REPLAY_DIR: &str = "replays";
+const GHOST_DIR: &str = "ghosts";
+const BESTS_DIR: &str = "bests";
+const WINDOW_SIZE: (u32, u32) = (600, 600);
 
 fn main() {
+    // speed-game [--replay <path>] [level.sgld | level.sglt | level dir]
+    let mut args = std::env::args().skip(1);
+    let mut replay = None;
+    let mut level_path = None;
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--replay" => {
+                let Some(path) = args.next() else {
+                    eprintln!("--replay needs a path to a replay file");
+                    std::process::exit(1);
+                };
+
+                match Replay::load(&path) {
+                    Ok(r) => replay = Some(r),
+                    Err(e) => {
+                        eprintln!("{}: {}", path, e);
+                        std::process::exit(1);
+                    },
+                }
+            },
+            _ if arg.starts_with("--") || level_path.is_some() => {
+                eprintln!("unknown argument `{}`", arg);
+                std::process::exit(1);
+            },
+            _ => level_path = Some(PathBuf::from(arg)),
+        }
+    }
+
+    // loaded before the window opens so a bad file doesnt flash one up
+    let levels = match level_path {
+        Some(path) => load_levels(&path).unwrap_or_else(|e| {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }),
+        None => builtin_levels(),
+    };
+
     let mut engine = EngineBuilder::new()
-        .with_resolution((600, 600))
+        .with_resolution(WINDOW_SIZE)
         .build()
         .unwrap();
 
-    let game = SpeedGame::new(&mut engine);
+    let game = SpeedGame::new(&mut engine, levels, replay);
 
     engine.run(game);
 }
 
 struct SpeedGame {
-    player: Character,
-    current_level: Level,
+    sim: Simulation,
+    player_sprite: PlayerSprite,
     camera: Camera,
     debug: DebugText,
+    hud: Hud,
+    checkpoint_effects: CheckpointEffects,
+    death_effect: DeathEffect,
+    timestep: FixedTimestep,
+    profile_watcher: ProfileWatcher,
+    bindings: Bindings,
+    input: Box<dyn InputSource>,
+    // None while watching a replay
+    recording: Option<Replay>,
+    ghost: Ghost,
+    best_ghost: Option<GhostPlayback>,
+    timer: SpeedrunTimer,
+    personal_best: PersonalBest,
+    // levels still to come, the finished one goes to the back
+    next_levels: Vec<Level>,
+    // the player is stopped on the results screen
+    finished: bool,
+    // deaths so far this run
+    deaths: u32,
 }
 
 impl SpeedGame {
-    pub fn new(engine: &mut Engine) -> Self {
-        let player = Character::new(engine);
+    /// `levels` are played in order and cant be empty
+    pub fn new(engine: &mut Engine, mut levels: Vec<Level>, replay: Option<Replay>) -> Self {
+        let mut profile_watcher = ProfileWatcher::new(MOVEMENT_PROFILE_PATH);
+        let profile = match profile_watcher.poll() {
+            Some(Ok(profile)) => profile,
+            Some(Err(e)) => {
+                eprintln!("{}, using the default movement", e);
+                MovementProfile::default()
+            },
+            None => MovementProfile::default(),
+        };
+
+        let bindings = match Bindings::load(CONTROLS_PATH) {
+            Ok(bindings) => bindings,
+            // no controls file yet so write the defaults out for people to edit
+            Err(BindingsError::Io(e)) if e.kind() == std::io::ErrorKind::NotFound => {
+                let bindings = Bindings::default();
+                if let Err(e) = bindings.save(CONTROLS_PATH) {
+                    eprintln!("could not write the default controls: {}", e);
+                }
+                bindings
+            },
+            Err(e) => {
+                eprintln!("{}, using the default controls", e);
+                Bindings::default()
+            },
+        };
+
+        let player_sprite = PlayerSprite::new(engine);
         let camera = Camera::new(engine);
         let debug = DebugText::new(engine);
+        let checkpoint_effects = CheckpointEffects::new(engine);
+        let death_effect = DeathEffect::new(engine);
+        let mut hud = Hud::new(engine, vec2!(WINDOW_SIZE.0 as f32, WINDOW_SIZE.1 as f32));
+
+        for level in &mut levels {
+            level.set_platform_mat(MaterialBuilder::new().build(engine));
+        }
+
+        // a replay starts on the level it was recorded on if its one of ours
+        let start = replay
+            .as_ref()
+            .and_then(|r| levels.iter().position(|l| l.content_hash() == r.get_level_hash()))
+            .unwrap_or(0);
+        levels.rotate_left(start);
+        let current_level = levels.remove(0);
+        let next_levels = levels;
 
-        let platform_material = MaterialBuilder::new().build(engine);
-        let current_level = Level::new(
-            vec![
-                Platform::new(vec2!(10.0, 200.0), vec2!(300.0, 100.0)),
-                Platform::new(vec2!(0.0, 600.0), vec2!(600.0, 50.0)),
-            ],
-            platform_material
-        );
+        let (input, recording): (Box<dyn InputSource>, _) = match replay {
+            Some(replay) => {
+                // still play it so the mismatch can be seen, just warn first
+                if let Err(e) = replay.check_level(&current_level) {
+                    eprintln!("{}", e);
+                }
+                if let Err(e) = replay.check_profile(&profile) {
+                    eprintln!("{}", e);
+                }
+                (Box::new(ReplayInput::new(replay)), None)
+            },
+            None => {
+                let recording = Replay::new(&current_level, &profile);
+                (Box::new(KeyboardInput::new(bindings.clone())), Some(recording))
+            },
+        };
+
+        hud.show_title(current_level.get_meta());
+        let ghost = Ghost::new(&current_level);
+        let best_ghost = Ghost::load_best(Path::new(GHOST_DIR), &current_level).map(GhostPlayback::new);
+        let personal_best = PersonalBest::load_or_default(Path::new(BESTS_DIR), current_level.content_hash());
 
         Self {
-            player,
-            current_level,
+            sim: Simulation::new(current_level, profile),
+            player_sprite,
             camera,
             debug,
+            hud,
+            checkpoint_effects,
+            death_effect,
+            timestep: FixedTimestep::default(),
+            profile_watcher,
+            input,
+            bindings,
+            recording,
+            ghost,
+            best_ghost,
+            timer: SpeedrunTimer::new(),
+            personal_best,
+            next_levels,
+            finished: false,
+            deaths: 0,
+        }
+    }
+
+    /// throws away the current run and starts again from the top
+    fn restart(&mut self) {
+        self.sim.restart();
+        self.input.rewind();
+        self.ghost = Ghost::new(self.sim.get_level());
+        if let Some(recording) = &mut self.recording {
+            *recording = Replay::new(self.sim.get_level(), self.sim.get_character().get_profile());
+        }
+        if let Some(best) = &mut self.best_ghost {
+            best.reset();
+        }
+        self.timer.reset();
+        self.hud.reset();
+        self.checkpoint_effects.reset();
+        self.death_effect.reset();
+        self.finished = false;
+        self.deaths = 0;
+    }
+
+    /// swaps in the next level and starts it fresh, with only one level this is just a restart
+    fn next_level(&mut self) {
+        if !self.next_levels.is_empty() {
+            let level = self.next_levels.remove(0);
+            let old = self.sim.set_level(level);
+            self.next_levels.push(old);
+
+            let level = self.sim.get_level();
+            self.best_ghost = Ghost::load_best(Path::new(GHOST_DIR), level).map(GhostPlayback::new);
+            self.personal_best = PersonalBest::load_or_default(Path::new(BESTS_DIR), level.content_hash());
+        }
+
+        self.restart();
+        self.hud.show_title(self.sim.get_level().get_meta());
+    }
+
+    /// the sim has already frozen the player and will respawn them on its own, this is
+    /// where anything the game shows for a death hangs off
+    fn player_died(&mut self) {
+        self.deaths += 1;
+        let player = self.sim.get_character();
+        self.death_effect.trigger(player.get_pos(), player.get_size());
+    }
+
+    /// splits the timer when a checkpoint is touched and shows how it compares to the best
+    fn checkpoint_reached(&mut self, entity_idx: usize) {
+        if let Some(entity) = self.sim.get_level().get_entities().get(entity_idx) {
+            self.checkpoint_effects.activate(entity.pos, entity.size);
+        }
+        if let Some(idx) = self.timer.split(entity_idx) {
+            let result = self.personal_best.compare_split(idx, self.timer.get_splits());
+            self.hud.show_split(result);
+        }
+    }
+
+    /// called when the player reaches the goal, stops the run and shows the results.
+    /// keeps the run as the ghost if it was a new best. watching a replay only shows
+    /// the results, it isnt the players run so nothing is saved
+    fn finish_run(&mut self) {
+        self.finished = true;
+        let live = self.recording.is_some();
+
+        let old_best = self.personal_best.run.as_ref().map(|r| r.total);
+        let ticks = match self.timer.finish() {
+            Some(run) if live => {
+                self.personal_best.record(&run);
+                if let Err(e) = self.personal_best.save(Path::new(BESTS_DIR), self.sim.get_level().content_hash()) {
+                    eprintln!("could not save personal best: {}", e);
+                }
+                run.total
+            },
+            Some(run) => run.total,
+            None => self.timer.get_ticks(),
+        };
+        let medal = self.sim.get_level().get_meta().medal_for(ticks_to_secs(ticks));
+        self.hud.show_results(ticks, old_best, self.deaths, medal, !self.next_levels.is_empty(), &self.bindings);
+
+        if !live {
+            return;
+        }
+
+        let dir = Path::new(GHOST_DIR);
+        match self.ghost.save_if_best(dir) {
+            Ok(true) => self.best_ghost = Some(GhostPlayback::new(self.ghost.clone())),
+            Ok(false) => {},
+            Err(e) => eprintln!("could not save ghost: {}", e),
+        }
+    }
+
+    // the player is frozen on the results screen so stop blending between ticks
+    fn render_alpha(&self) -> f32 {
+        if self.finished {
+            1.0
+        } else {
+            self.timestep.alpha()
+        }
+    }
+
+    fn save_replay(&self) {
+        let Some(recording) = &self.recording else {
+            return;
+        };
+
+        let secs = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default();
+        let path = Path::new(REPLAY_DIR).join(format!("run-{}.sgrp", secs));
+
+        let result = std::fs::create_dir_all(REPLAY_DIR).and_then(|_| recording.save(&path));
+        match result {
+            Ok(_) => println!("saved replay to {}", path.display()),
+            Err(e) => eprintln!("could not save replay: {}", e),
         }
     }
 }
@@ -59,18 +315,160 @@ impl Game for SpeedGame {
     fn update(&mut self, engine: &mut Engine) {
         let dt = engine.get_frame_delta_time();
 
-        self.player.update(dt, engine, &self.current_level);
-        self.camera.center = self.player.get_cetner();
-        self.debug.update_player_info(&self.player);
+        // lets designers tweak the feel while the game is running. a replay has to keep the
+        // movement it was recorded with, and a run being recorded starts over so the
+        // replay and any best time only ever come from one set of numbers
+        if self.recording.is_some() {
+            match self.profile_watcher.poll() {
+                Some(Ok(profile)) => {
+                    self.sim.get_character_mut().set_profile(profile);
+                    self.restart();
+                },
+                Some(Err(e)) => eprintln!("{}, keeping the old movement", e),
+                None => {},
+            }
+        }
+
+        self.input.begin_frame(engine);
+        let ticks = self.timestep.advance(dt);
+        for _ in 0..ticks {
+            if self.finished {
+                break;
+            }
+
+            let held = self.input.next_tick();
+            if let Some(recording) = &mut self.recording {
+                recording.record(held);
+            }
+
+            self.timer.tick(held);
+            self.sim.step(held);
+            // the ghost runs on the timers clock so picking the shortest ghost picks the best time
+            if self.timer.get_state() == TimerState::Running {
+                self.ghost.record(self.sim.get_character());
+            }
+
+            // by index since handling an event needs the sim mutably
+            for idx in 0..self.sim.get_events().len() {
+                match self.sim.get_events()[idx] {
+                    SimEvent::Died => self.player_died(),
+                    SimEvent::CheckpointReached(idx) => self.checkpoint_reached(idx),
+                    SimEvent::Respawned => {},
+                }
+            }
+
+            if self.sim.reached_goal() {
+                self.finish_run();
+            }
+        }
+
+        if self.bindings.is_pressed(engine, Action::Restart) {
+            self.restart();
+        } else if self.finished && self.bindings.is_pressed(engine, Action::NextLevel) {
+            self.next_level();
+        }
+
+        if self.bindings.is_pressed(engine, Action::SaveReplay) {
+            self.save_replay();
+        }
+
+        self.camera.center = self.sim.get_character().get_interpolated_center(self.render_alpha());
+        self.debug.update_player_info(self.sim.get_character());
         self.debug.update_engine_info(engine, dt);
-        self.debug.prepare(engine);
+        self.debug.prepare(engine, &self.bindings);
+
+        let split = self.best_ghost
+            .as_mut()
+            .and_then(|g| g.split(self.sim.get_character().get_pos(), self.timer.get_ticks()));
+        self.hud.set_ghost_delta(split);
+        self.hud.set_timer(self.timer.get_ticks());
+        self.hud.prepare(engine, dt);
+        self.checkpoint_effects.update(dt);
+        self.death_effect.update(dt);
     }
 
     fn render<'pass, 'others>(&'others mut self, mut renderer: RenderInformation<'pass, 'others>) where 'others: 'pass {
+        let alpha = self.render_alpha();
+        let player_pos = self.sim.get_character().get_interpolated_pos(alpha);
+        let ghost_pos = self.best_ghost.as_ref().and_then(|g| g.pos_at(self.timer.get_ticks(), alpha));
+        let dead = self.sim.is_dead();
+
         self.camera.set_active(&mut renderer);
-        self.current_level.draw(&mut renderer);
-        self.player.draw(&mut renderer);
+        self.sim.get_level_mut().draw(&mut renderer);
+        self.checkpoint_effects.draw(&mut renderer);
+        self.death_effect.draw(&mut renderer);
+        self.player_sprite.draw(&mut renderer, player_pos, ghost_pos, dead);
 
+        self.hud.draw(&mut renderer);
         self.debug.draw(&mut renderer);
     }
-}
\ No newline at end of file
+}
+/// a single level file or every `.sgld` and `.sglt` in a directory, played in file name order.
+/// broken files in a directory are skipped so one bad level doesnt stop the rest
+fn load_levels(path: &Path) -> Result<Vec<Level>, String> {
+    if !path.is_dir() {
+        return load_checked(path).map(|level| vec![level]);
+    }
+
+    let mut paths = std::fs::read_dir(path)
+        .map_err(|e| format!("could not read level directory {}: {}", path.display(), e))?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|p| p.extension().is_some_and(|ext| ext == "sgld" || ext == TEXT_EXTENSION))
+        .collect::<Vec<PathBuf>>();
+    paths.sort();
+
+    let mut levels = Vec::with_capacity(paths.len());
+    for p in paths {
+        match load_checked(&p) {
+            Ok(level) => levels.push(level),
+            Err(e) => eprintln!("skipping {}", e),
+        }
+    }
+
+    if levels.is_empty() {
+        return Err(format!("no levels found in {}", path.display()));
+    }
+
+    Ok(levels)
+}
+
+/// loads and validates a level, warnings are printed and errors mean it cant be played
+fn load_checked(path: &Path) -> Result<Level, String> {
+    let level = Level::load(path).map_err(|e| format!("level {}: {}", path.display(), e))?;
+
+    let diagnostics = level.validate();
+    for diagnostic in &diagnostics {
+        eprintln!("{}: {}", path.display(), diagnostic);
+    }
+
+    if diagnostics.iter().any(|d| d.is_error()) {
+        return Err(format!("level {}: has errors", path.display()));
+    }
+
+    Ok(level)
+}
+
+/// the levels played when none are given on the command line, in play order
+fn builtin_levels() -> Vec<Level> {
+    let mut first = Level::headless(vec![
+        Platform::new(vec2!(10.0, 200.0), vec2!(300.0, 100.0)),
+        Platform::new(vec2!(0.0, 600.0), vec2!(600.0, 50.0)),
+    ]);
+    first.set_player_start(vec2!(40.0, 86.0));
+    first.add_entity(Entity::new(EntityKind::Goal, vec2!(0.0, 480.0), vec2!(80.0, 120.0)));
+    first.get_mut_meta().name = String::from("First Steps");
+    first.get_mut_meta().difficulty = Some(Difficulty::Easy);
+
+    let mut second = Level::headless(vec![
+        Platform::new(vec2!(0.0, 600.0), vec2!(500.0, 50.0)),
+        Platform::new(vec2!(650.0, 520.0), vec2!(200.0, 40.0)),
+        Platform::new(vec2!(1000.0, 600.0), vec2!(400.0, 50.0)),
+    ]);
+    second.set_player_start(vec2!(20.0, 486.0));
+    second.add_entity(Entity::new(EntityKind::Checkpoint, vec2!(700.0, 400.0), vec2!(100.0, 120.0)));
+    second.add_entity(Entity::new(EntityKind::Goal, vec2!(1280.0, 480.0), vec2!(100.0, 120.0)));
+    second.get_mut_meta().name = String::from("Gap Jump");
+    second.get_mut_meta().difficulty = Some(Difficulty::Easy);
+
+    vec![first, second]
+}
@@ -0,0 +1,267 @@
+use std::fmt::Display;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use utils::config::{parse_entries, ConfigEntry, ConfigError};
+use utils::hash::fnv1a;
+
+use crate::character::DashConfig;
+use crate::jump_assist::JumpAssistConfig;
+
+/// every number that decides how the player moves. loaded from a settings file so it
+/// can be tuned without recompiling, and swapped out whole for abilities or upgrades
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MovementProfile {
+    pub acceleration: f32,
+    pub deceleration: f32,
+    pub turn_speed: f32,
+    pub max_speed: f32,
+
+    pub air_acceleration: f32,
+    pub air_deceleration: f32,
+    pub air_turn_speed: f32,
+    pub max_air_speed: f32,
+
+    pub fall_acceleration: f32,
+    pub max_fall_speed: f32,
+    /// how high a full jump goes in pixels
+    pub jump_height: f32,
+    /// upwards speed taken away when jump is let go early
+    pub jump_cut: f32,
+
+    pub wall_slide_speed: f32,
+    pub wall_jump_push: f32,
+
+    pub dash: DashConfig,
+    pub jump_assist: JumpAssistConfig,
+}
+
+impl MovementProfile {
+    /// starts from the defaults and overrides whatever the text sets
+    pub fn parse(text: &str) -> Result<Self, ConfigError> {
+        let mut profile = Self::default();
+
+        for entry in parse_entries(text)? {
+            match entry.key {
+                "acceleration" => profile.acceleration = parse_amount(&entry)?,
+                "deceleration" => profile.deceleration = parse_amount(&entry)?,
+                "turn_speed" => profile.turn_speed = parse_amount(&entry)?,
+                "max_speed" => profile.max_speed = parse_amount(&entry)?,
+                "air_acceleration" => profile.air_acceleration = parse_amount(&entry)?,
+                "air_deceleration" => profile.air_deceleration = parse_amount(&entry)?,
+                "air_turn_speed" => profile.air_turn_speed = parse_amount(&entry)?,
+                "max_air_speed" => profile.max_air_speed = parse_amount(&entry)?,
+                "fall_acceleration" => profile.fall_acceleration = parse_amount(&entry)?,
+                "max_fall_speed" => profile.max_fall_speed = parse_amount(&entry)?,
+                "jump_height" => profile.jump_height = parse_amount(&entry)?,
+                "jump_cut" => profile.jump_cut = parse_amount(&entry)?,
+                "wall_slide_speed" => profile.wall_slide_speed = parse_amount(&entry)?,
+                "wall_jump_push" => profile.wall_jump_push = parse_amount(&entry)?,
+                "dash_speed" => profile.dash.speed = parse_amount(&entry)?,
+                "dash_duration" => profile.dash.duration = parse_amount(&entry)?,
+                "dash_cooldown" => profile.dash.cooldown = parse_amount(&entry)?,
+                "dash_air_charges" => profile.dash.air_charges = entry.parse_u32()?,
+                "coyote_time" => profile.jump_assist.coyote_time = parse_amount(&entry)?,
+                "jump_buffer" => profile.jump_assist.jump_buffer = parse_amount(&entry)?,
+                _ => return Err(entry.unknown_key()),
+            }
+        }
+
+        Ok(profile)
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, ProfileError> {
+        let text = std::fs::read_to_string(path)?;
+        Ok(Self::parse(&text)?)
+    }
+
+    /// changes whenever any number in the profile does, stored in replays so
+    /// they can tell they are being played back with different movement
+    pub fn content_hash(&self) -> u64 {
+        fnv1a(self.to_string().as_bytes())
+    }
+
+    /// upwards speed needed to reach `jump_height`
+    pub fn jump_speed(&self) -> f32 {
+        -(2.0 * self.fall_acceleration * self.jump_height).sqrt()
+    }
+
+    pub(crate) fn ground_constants(&self) -> [f32; 4] {
+        [self.turn_speed, self.acceleration, self.deceleration, self.max_speed]
+    }
+
+    pub(crate) fn air_constants(&self) -> [f32; 4] {
+        [self.air_turn_speed, self.air_acceleration, self.air_deceleration, self.max_air_speed]
+    }
+}
+
+impl Default for MovementProfile {
+    fn default() -> Self {
+        Self {
+            acceleration: 190.0,
+            deceleration: 100.0,
+            turn_speed: 250.0,
+            max_speed: 200.0,
+            air_acceleration: 145.0,
+            air_deceleration: 50.0,
+            air_turn_speed: 150.0,
+            max_air_speed: 200.0,
+            fall_acceleration: 80.0,
+            max_fall_speed: 200.0,
+            jump_height: 100.0,
+            jump_cut: 40.0,
+            wall_slide_speed: 60.0,
+            wall_jump_push: 200.0,
+            dash: DashConfig::default(),
+            jump_assist: JumpAssistConfig::default(),
+        }
+    }
+}
+
+/// writes the profile back out in the same format `parse` reads
+impl Display for MovementProfile {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "# ground")?;
+        writeln!(f, "acceleration = {}", self.acceleration)?;
+        writeln!(f, "deceleration = {}", self.deceleration)?;
+        writeln!(f, "turn_speed = {}", self.turn_speed)?;
+        writeln!(f, "max_speed = {}", self.max_speed)?;
+        writeln!(f, "\n# air")?;
+        writeln!(f, "air_acceleration = {}", self.air_acceleration)?;
+        writeln!(f, "air_deceleration = {}", self.air_deceleration)?;
+        writeln!(f, "air_turn_speed = {}", self.air_turn_speed)?;
+        writeln!(f, "max_air_speed = {}", self.max_air_speed)?;
+        writeln!(f, "fall_acceleration = {}", self.fall_acceleration)?;
+        writeln!(f, "max_fall_speed = {}", self.max_fall_speed)?;
+        writeln!(f, "jump_height = {}", self.jump_height)?;
+        writeln!(f, "jump_cut = {}", self.jump_cut)?;
+        writeln!(f, "\n# walls")?;
+        writeln!(f, "wall_slide_speed = {}", self.wall_slide_speed)?;
+        writeln!(f, "wall_jump_push = {}", self.wall_jump_push)?;
+        writeln!(f, "\n# dash")?;
+        writeln!(f, "dash_speed = {}", self.dash.speed)?;
+        writeln!(f, "dash_duration = {}", self.dash.duration)?;
+        writeln!(f, "dash_cooldown = {}", self.dash.cooldown)?;
+        writeln!(f, "dash_air_charges = {}", self.dash.air_charges)?;
+        writeln!(f, "\n# timing windows in seconds")?;
+        writeln!(f, "coyote_time = {}", self.jump_assist.coyote_time)?;
+        writeln!(f, "jump_buffer = {}", self.jump_assist.jump_buffer)
+    }
+}
+
+// every number in a profile is a speed, distance or time so none of them can go bellow zero,
+// and NaN or inf would spread through the physics without anything crashing
+fn parse_amount(entry: &ConfigEntry) -> Result<f32, ConfigError> {
+    let value = entry.parse_f32()?;
+    if !value.is_finite() || value < 0.0 {
+        return Err(ConfigError::new(entry.line, format!("`{}` has to be a finite number zero or more but got `{}`", entry.key, entry.value)));
+    }
+
+    Ok(value)
+}
+
+#[derive(Debug)]
+pub enum ProfileError {
+    Io(std::io::Error),
+    Parse(ConfigError),
+}
+
+impl Display for ProfileError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "could not read movement profile: {}", e),
+            Self::Parse(e) => write!(f, "bad movement profile: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for ProfileError {}
+
+impl From<std::io::Error> for ProfileError {
+    fn from(value: std::io::Error) -> Self {
+        Self::Io(value)
+    }
+}
+
+impl From<ConfigError> for ProfileError {
+    fn from(value: ConfigError) -> Self {
+        Self::Parse(value)
+    }
+}
+
+/// reloads a profile whenever its file changes on disk
+#[derive(Debug)]
+pub struct ProfileWatcher {
+    path: PathBuf,
+    last_modified: Option<SystemTime>,
+}
+
+impl ProfileWatcher {
+    pub fn new<P: Into<PathBuf>>(path: P) -> Self {
+        Self {
+            path: path.into(),
+            last_modified: None,
+        }
+    }
+
+    /// returns a freshly loaded profile if the file was changed since the last poll
+    pub fn poll(&mut self) -> Option<Result<MovementProfile, ProfileError>> {
+        let modified = std::fs::metadata(&self.path)
+            .and_then(|m| m.modified())
+            .ok()?;
+
+        if self.last_modified == Some(modified) {
+            return None;
+        }
+
+        self.last_modified = Some(modified);
+        Some(MovementProfile::load(&self.path))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn defaults_round_trip_through_text() {
+        let profile = MovementProfile::default();
+        let parsed = MovementProfile::parse(&profile.to_string()).unwrap();
+
+        assert_eq!(profile, parsed);
+    }
+
+    #[test]
+    fn partial_file_keeps_defaults() {
+        let profile = MovementProfile::parse("max_speed = 300\ndash_air_charges = 2").unwrap();
+
+        assert_eq!(profile.max_speed, 300.0);
+        assert_eq!(profile.dash.air_charges, 2);
+        assert_eq!(profile.acceleration, MovementProfile::default().acceleration);
+    }
+
+    #[test]
+    fn unknown_keys_are_errors() {
+        let err = MovementProfile::parse("max_sped = 300").unwrap_err();
+        assert_eq!(err.line, 1);
+    }
+
+    #[test]
+    fn nonsense_numbers_are_errors() {
+        for bad in ["NaN", "inf", "-inf", "-1"] {
+            let err = MovementProfile::parse(&format!("max_speed = 300\njump_height = {}", bad)).unwrap_err();
+            assert_eq!(err.line, 2, "`{}` should be rejected", bad);
+        }
+
+        assert_eq!(MovementProfile::parse("coyote_time = 0").unwrap().jump_assist.coyote_time, 0.0);
+    }
+
+    #[test]
+    fn jump_speed_matches_height() {
+        let profile = MovementProfile::default();
+        // v^2 = 2gh
+        let v = profile.jump_speed();
+        assert!((v * v - 2.0 * profile.fall_acceleration * profile.jump_height).abs() < 0.01);
+        assert!(v < 0.0);
+    }
+}
@@ -0,0 +1,364 @@
+use std::fmt::Display;
+use std::path::Path;
+
+use level_editor::level::Level;
+
+use crate::input::{ActionSet, InputSource};
+use crate::movement::MovementProfile;
+
+// SGRP in bytes
+const FILE_HEADER: [u8; 4] = [115, 103, 114, 112];
+// version 2 added the movement profile hash after the level hash
+const VERSION: u16 = 2;
+// header + version + level hash + tick count, version 2 adds a profile hash
+const V1_PREAMBLE_LEN: usize = 4 + 2 + 8 + 4;
+const PREAMBLE_LEN: usize = 4 + 2 + 8 + 8 + 4;
+
+/// every tick of input from a run plus the level and movement it was played with
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Replay {
+    level_hash: u64,
+    // None for version 1 replays which didnt store it
+    profile_hash: Option<u64>,
+    ticks: Vec<ActionSet>,
+}
+
+impl Replay {
+    pub fn new(level: &Level, profile: &MovementProfile) -> Self {
+        Self {
+            level_hash: level.content_hash(),
+            profile_hash: Some(profile.content_hash()),
+            ticks: Vec::new(),
+        }
+    }
+
+    pub fn record(&mut self, actions: ActionSet) {
+        self.ticks.push(actions);
+    }
+
+    pub fn get_ticks(&self) -> &[ActionSet] {
+        &self.ticks
+    }
+
+    pub fn get_level_hash(&self) -> u64 {
+        self.level_hash
+    }
+
+    /// makes sure `level` is the one this was recorded on, playing a replay on
+    /// a different level will desync almost straight away
+    pub fn check_level(&self, level: &Level) -> Result<(), ReplayError> {
+        let found = level.content_hash();
+        if found != self.level_hash {
+            return Err(ReplayError::LevelMismatch { expected: self.level_hash, found });
+        }
+
+        Ok(())
+    }
+
+    /// makes sure the player moves with the same numbers the run was recorded with, a
+    /// tweaked movement.cfg desyncs just like an edited level. old replays cant be checked
+    pub fn check_profile(&self, profile: &MovementProfile) -> Result<(), ReplayError> {
+        let found = profile.content_hash();
+        match self.profile_hash {
+            Some(expected) if expected != found => Err(ReplayError::ProfileMismatch { expected, found }),
+            _ => Ok(()),
+        }
+    }
+
+    /// held inputs barely change between ticks so they are stored as runs of (length, actions)
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut runs: Vec<(u16, ActionSet)> = Vec::new();
+        for actions in &self.ticks {
+            match runs.last_mut() {
+                Some((len, last)) if last == actions && *len < u16::MAX => *len += 1,
+                _ => runs.push((1, *actions)),
+            }
+        }
+
+        let mut buffer = Vec::with_capacity(PREAMBLE_LEN + runs.len() * 4);
+        buffer.extend(FILE_HEADER);
+        buffer.extend(VERSION.to_le_bytes());
+        buffer.extend(self.level_hash.to_le_bytes());
+        buffer.extend(self.profile_hash.unwrap_or_default().to_le_bytes());
+        buffer.extend((self.ticks.len() as u32).to_le_bytes());
+
+        for (len, actions) in runs {
+            buffer.extend(len.to_le_bytes());
+            buffer.extend(actions.bits().to_le_bytes());
+        }
+
+        buffer
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ReplayError> {
+        if bytes.len() < V1_PREAMBLE_LEN {
+            return Err(ReplayError::Truncated);
+        }
+
+        if bytes[0..4] != FILE_HEADER {
+            return Err(ReplayError::BadMagic);
+        }
+
+        let version = u16::from_le_bytes([bytes[4], bytes[5]]);
+        let preamble_len = match version {
+            1 => V1_PREAMBLE_LEN,
+            VERSION => PREAMBLE_LEN,
+            _ => return Err(ReplayError::UnsupportedVersion(version)),
+        };
+        if bytes.len() < preamble_len {
+            return Err(ReplayError::Truncated);
+        }
+
+        let level_hash = u64::from_le_bytes(bytes[6..14].try_into().unwrap());
+        let profile_hash = (version >= 2).then(|| u64::from_le_bytes(bytes[14..22].try_into().unwrap()));
+        let tick_count = u32::from_le_bytes(bytes[preamble_len - 4..preamble_len].try_into().unwrap()) as usize;
+
+        let runs = &bytes[preamble_len..];
+        if !runs.len().is_multiple_of(4) {
+            return Err(ReplayError::Truncated);
+        }
+
+        // the tick count in the header cant be trusted to size anything, the runs
+        // are what actually backs it so they have to add up first
+        let run_ticks = runs
+            .chunks_exact(4)
+            .map(|run| u16::from_le_bytes([run[0], run[1]]) as usize)
+            .sum::<usize>();
+        if run_ticks != tick_count {
+            return Err(ReplayError::Truncated);
+        }
+
+        let mut ticks = Vec::with_capacity(run_ticks);
+        for run in runs.chunks_exact(4) {
+            let len = u16::from_le_bytes([run[0], run[1]]) as usize;
+            let actions = ActionSet::from_bits(u16::from_le_bytes([run[2], run[3]]));
+            ticks.extend(std::iter::repeat_n(actions, len));
+        }
+
+        Ok(Self {
+            level_hash,
+            profile_hash,
+            ticks,
+        })
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), std::io::Error> {
+        std::fs::write(path, self.to_bytes())
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, ReplayError> {
+        let bytes = std::fs::read(path)?;
+        Self::from_bytes(&bytes)
+    }
+}
+
+/// feeds a replay back in as if it were being played, holding nothing after the last tick
+#[derive(Clone, Debug)]
+pub struct ReplayInput {
+    replay: Replay,
+    position: usize,
+}
+
+impl ReplayInput {
+    pub fn new(replay: Replay) -> Self {
+        Self {
+            replay,
+            position: 0,
+        }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.position >= self.replay.ticks.len()
+    }
+}
+
+impl InputSource for ReplayInput {
+    fn next_tick(&mut self) -> ActionSet {
+        let actions = self.replay.ticks.get(self.position).copied().unwrap_or_default();
+        self.position += 1;
+        actions
+    }
+
+    fn rewind(&mut self) {
+        self.position = 0;
+    }
+}
+
+#[derive(Debug)]
+pub enum ReplayError {
+    Io(std::io::Error),
+    BadMagic,
+    UnsupportedVersion(u16),
+    Truncated,
+    LevelMismatch {
+        expected: u64,
+        found: u64,
+    },
+    ProfileMismatch {
+        expected: u64,
+        found: u64,
+    },
+}
+
+impl Display for ReplayError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "could not read replay: {}", e),
+            Self::BadMagic => write!(f, "not a replay file"),
+            Self::UnsupportedVersion(v) => write!(f, "replay version {} is not supported", v),
+            Self::Truncated => write!(f, "replay file is cut short"),
+            Self::LevelMismatch { expected, found } => write!(
+                f,
+                "replay was recorded on level {:016x} but this level is {:016x}, playback will not match",
+                expected,
+                found
+            ),
+            Self::ProfileMismatch { expected, found } => write!(
+                f,
+                "replay was recorded with movement {:016x} but the current movement is {:016x}, playback will not match",
+                expected,
+                found
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ReplayError {}
+
+impl From<std::io::Error> for ReplayError {
+    fn from(value: std::io::Error) -> Self {
+        Self::Io(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::Action;
+    use crate::movement::MovementProfile;
+    use crate::sim::Simulation;
+
+    use bottomless_pit::vec2;
+    use level_editor::level::Platform;
+
+    fn test_level() -> Level {
+        Level::headless(vec![
+            Platform::new(vec2!(10.0, 200.0), vec2!(300.0, 100.0)),
+            Platform::new(vec2!(0.0, 600.0), vec2!(600.0, 50.0)),
+        ])
+    }
+
+    fn recorded_run(level: &Level) -> Replay {
+        let right = ActionSet::empty().with(Action::MoveRight);
+        let mut replay = Replay::new(level, &MovementProfile::default());
+
+        for tick in 0..600 {
+            let actions = match tick {
+                0..=199 => ActionSet::empty(),
+                200..=229 => right.with(Action::Jump),
+                230..=234 => right.with(Action::Dash),
+                _ => right,
+            };
+            replay.record(actions);
+        }
+
+        replay
+    }
+
+    #[test]
+    fn round_trips_through_bytes() {
+        let replay = recorded_run(&test_level());
+        let bytes = replay.to_bytes();
+
+        assert_eq!(Replay::from_bytes(&bytes).unwrap(), replay);
+        // 4 runs of input
+        assert_eq!(bytes.len(), PREAMBLE_LEN + 4 * 4);
+    }
+
+    #[test]
+    fn bad_files_are_rejected() {
+        let bytes = recorded_run(&test_level()).to_bytes();
+
+        assert!(matches!(Replay::from_bytes(b"sgld"), Err(ReplayError::Truncated)));
+        assert!(matches!(Replay::from_bytes(&bytes[..bytes.len() - 4]), Err(ReplayError::Truncated)));
+
+        let mut wrong_magic = bytes.clone();
+        wrong_magic[0] = b'x';
+        assert!(matches!(Replay::from_bytes(&wrong_magic), Err(ReplayError::BadMagic)));
+
+        let mut wrong_version = bytes;
+        wrong_version[4] = 99;
+        assert!(matches!(Replay::from_bytes(&wrong_version), Err(ReplayError::UnsupportedVersion(99))));
+    }
+
+    #[test]
+    fn tick_count_has_to_match_the_runs() {
+        let mut bytes = recorded_run(&test_level()).to_bytes();
+        // a huge count with nothing behind it must not be allocated up front
+        bytes[PREAMBLE_LEN - 4..PREAMBLE_LEN].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert!(matches!(Replay::from_bytes(&bytes), Err(ReplayError::Truncated)));
+        assert!(matches!(Replay::from_bytes(&bytes[..PREAMBLE_LEN]), Err(ReplayError::Truncated)));
+    }
+
+    #[test]
+    fn version_one_replays_still_load() {
+        let replay = recorded_run(&test_level());
+        let mut bytes = replay.to_bytes();
+        bytes[4..6].copy_from_slice(&1u16.to_le_bytes());
+        bytes.drain(14..22);
+
+        let loaded = Replay::from_bytes(&bytes).unwrap();
+        assert_eq!(loaded.get_ticks(), replay.get_ticks());
+        // nothing to compare against so any movement is allowed
+        let mut tweaked = MovementProfile::default();
+        tweaked.jump_height += 1.0;
+        assert!(loaded.check_profile(&tweaked).is_ok());
+    }
+
+    #[test]
+    fn edited_level_is_reported() {
+        let replay = recorded_run(&test_level());
+        assert!(replay.check_level(&test_level()).is_ok());
+
+        let edited = Level::headless(vec![
+            Platform::new(vec2!(10.0, 210.0), vec2!(300.0, 100.0)),
+            Platform::new(vec2!(0.0, 600.0), vec2!(600.0, 50.0)),
+        ]);
+        assert!(matches!(replay.check_level(&edited), Err(ReplayError::LevelMismatch { .. })));
+    }
+
+    #[test]
+    fn tweaked_movement_is_reported() {
+        let replay = Replay::from_bytes(&recorded_run(&test_level()).to_bytes()).unwrap();
+        assert!(replay.check_profile(&MovementProfile::default()).is_ok());
+
+        let mut tweaked = MovementProfile::default();
+        tweaked.max_speed += 10.0;
+        assert!(matches!(replay.check_profile(&tweaked), Err(ReplayError::ProfileMismatch { .. })));
+    }
+
+    #[test]
+    fn playback_matches_the_recorded_run() {
+        let replay = recorded_run(&test_level());
+
+        let mut live = Simulation::new(test_level(), MovementProfile::default());
+        for actions in replay.get_ticks() {
+            live.step(*actions);
+        }
+
+        let loaded = Replay::from_bytes(&replay.to_bytes()).unwrap();
+        let mut playback = Simulation::new(test_level(), MovementProfile::default());
+        let mut input = ReplayInput::new(loaded);
+        playback.run(&mut input, replay.get_ticks().len() as u64);
+
+        assert!(input.is_finished());
+        assert_eq!(playback.get_character().get_pos(), live.get_character().get_pos());
+        assert_eq!(playback.get_character().get_state(), live.get_character().get_state());
+
+        // restarting part way through plays the same run again from the top
+        playback.restart();
+        input.rewind();
+        playback.run(&mut input, replay.get_ticks().len() as u64);
+        assert_eq!(playback.get_character().get_pos(), live.get_character().get_pos());
+    }
+}
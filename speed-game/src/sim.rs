@@ -0,0 +1,508 @@
+use bottomless_pit::vectors::Vec2;
+use level_editor::entity::EntityKind;
+use level_editor::level::Level;
+
+use crate::character::{Character, PlayerState};
+use crate::input::{ActionSet, InputSource, InputState};
+use crate::movement::MovementProfile;
+use crate::timestep::{FIXED_DT, TICK_RATE};
+
+// how long the player stays dead before coming back in seconds
+const RESPAWN_DELAY: f32 = 0.75;
+
+/// things that happened during the last tick that the game might want to react to
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SimEvent {
+    Died,
+    Respawned,
+    /// the first touch of a checkpoint, holds its index in the levels entities
+    CheckpointReached(usize),
+}
+
+/// a level and the player stepped together one fixed tick at a time. needs no
+/// window so it can be driven by tests, replays or bots
+#[derive(Debug)]
+pub struct Simulation {
+    character: Character,
+    level: Level,
+    input: InputState,
+    ticks: u64,
+    // where to come back after dying, None is the levels player start
+    respawn_point: Option<Vec2<f32>>,
+    // entity indices of checkpoints touched since the last restart, dying keeps them
+    active_checkpoints: Vec<usize>,
+    // ticks left until a dead player respawns
+    respawn_in: u32,
+    events: Vec<SimEvent>,
+}
+
+impl Simulation {
+    pub fn new(level: Level, profile: MovementProfile) -> Self {
+        Self {
+            character: Character::new(profile, level.get_player_start()),
+            level,
+            input: InputState::default(),
+            ticks: 0,
+            respawn_point: None,
+            active_checkpoints: Vec::new(),
+            respawn_in: 0,
+            events: Vec::new(),
+        }
+    }
+
+    /// puts the player back at the start as if the level was just loaded
+    pub fn restart(&mut self) {
+        let profile = *self.character.get_profile();
+        self.character = Character::new(profile, self.level.get_player_start());
+        self.input = InputState::default();
+        self.ticks = 0;
+        self.respawn_point = None;
+        self.active_checkpoints.clear();
+        self.respawn_in = 0;
+        self.events.clear();
+    }
+
+    /// swaps the level out and restarts on it, handing back the old one
+    pub fn set_level(&mut self, level: Level) -> Level {
+        let old = std::mem::replace(&mut self.level, level);
+        self.restart();
+        old
+    }
+
+    /// runs a single tick with `held` as the input
+    pub fn step(&mut self, held: ActionSet) {
+        self.events.clear();
+        self.input.advance(held);
+
+        if self.is_dead() {
+            self.respawn_in = self.respawn_in.saturating_sub(1);
+            if self.respawn_in == 0 {
+                self.respawn();
+            }
+        } else {
+            self.character.update(FIXED_DT, &self.input, &self.level);
+            if self.touching_hazard() {
+                self.character.kill();
+                self.respawn_in = (RESPAWN_DELAY * TICK_RATE as f32) as u32;
+                self.events.push(SimEvent::Died);
+            } else {
+                self.check_checkpoints();
+            }
+        }
+
+        self.ticks += 1;
+    }
+
+    fn respawn(&mut self) {
+        let pos = self.respawn_point.unwrap_or(self.level.get_player_start());
+        self.character.respawn(pos);
+        self.events.push(SimEvent::Respawned);
+    }
+
+    fn check_checkpoints(&mut self) {
+        let pos = self.character.get_pos();
+        let size = self.character.get_size();
+
+        for (idx, entity) in self.level.get_entities().iter().enumerate() {
+            if entity.kind != EntityKind::Checkpoint || self.active_checkpoints.contains(&idx) || !entity.touches(pos, size) {
+                continue;
+            }
+
+            self.active_checkpoints.push(idx);
+            self.respawn_point = Some(entity.standing_pos(size));
+            self.events.push(SimEvent::CheckpointReached(idx));
+        }
+    }
+
+    fn touching_hazard(&self) -> bool {
+        let pos = self.character.get_pos();
+        let size = self.character.get_size();
+
+        let fell_out = self.level.get_death_y().is_some_and(|y| pos.y + size.y > y);
+        fell_out || self.level
+            .get_entities()
+            .iter()
+            .any(|e| e.kind.is_deadly() && e.touches(pos, size))
+    }
+
+    /// runs `ticks` ticks pulling input from `source`
+    pub fn run(&mut self, source: &mut dyn InputSource, ticks: u64) {
+        for _ in 0..ticks {
+            self.step(source.next_tick());
+        }
+    }
+
+    /// true once the player is touching any goal in the level
+    pub fn reached_goal(&self) -> bool {
+        if self.is_dead() {
+            return false;
+        }
+
+        let pos = self.character.get_pos();
+        let size = self.character.get_size();
+
+        self.level
+            .get_entities()
+            .iter()
+            .any(|e| e.kind == EntityKind::Goal && e.touches(pos, size))
+    }
+
+    pub fn is_dead(&self) -> bool {
+        self.character.get_state() == PlayerState::Dead
+    }
+
+    /// entity indices of the checkpoints touched so far, in the order they were reached
+    pub fn get_active_checkpoints(&self) -> &[usize] {
+        &self.active_checkpoints
+    }
+
+    /// what happened during the last `step`
+    pub fn get_events(&self) -> &[SimEvent] {
+        &self.events
+    }
+
+    pub fn get_character(&self) -> &Character {
+        &self.character
+    }
+
+    pub fn get_character_mut(&mut self) -> &mut Character {
+        &mut self.character
+    }
+
+    pub fn get_level(&self) -> &Level {
+        &self.level
+    }
+
+    pub fn get_level_mut(&mut self) -> &mut Level {
+        &mut self.level
+    }
+
+    pub fn get_input(&self) -> &InputState {
+        &self.input
+    }
+
+    /// ticks run since the simulation started
+    pub fn get_ticks(&self) -> u64 {
+        self.ticks
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::{Action, ScriptedInput};
+
+    use bottomless_pit::vec2;
+    use level_editor::entity::Entity;
+    use level_editor::level::Platform;
+
+    fn seconds(s: u64) -> u64 {
+        s * TICK_RATE as u64
+    }
+
+    // the same layout as the first level in the game, spawning in the air above it
+    fn test_level() -> Level {
+        let mut level = Level::headless(vec![
+            Platform::new(vec2!(10.0, 200.0), vec2!(300.0, 100.0)),
+            Platform::new(vec2!(0.0, 600.0), vec2!(600.0, 50.0)),
+        ]);
+        level.set_player_start(vec2!(0.0, -200.0));
+        level
+    }
+
+    fn standing_on(sim: &Simulation) -> Option<usize> {
+        let player = sim.get_character();
+        let feet = player.get_pos().y + player.get_size().y;
+
+        sim.get_level()
+            .get_platforms()
+            .iter()
+            .position(|p| (p.pos.y - feet).abs() < 0.01 &&
+                player.get_pos().x < p.pos.x + p.size.x &&
+                player.get_pos().x + player.get_size().x > p.pos.x)
+    }
+
+    #[test]
+    fn spawns_and_restarts_at_the_player_start() {
+        let mut level = test_level();
+        level.set_player_start(vec2!(100.0, 86.0));
+        let mut sim = Simulation::new(level, MovementProfile::default());
+        assert_eq!(sim.get_character().get_pos(), vec2!(100.0, 86.0));
+
+        sim.run(&mut ScriptedInput::default().hold(ActionSet::empty().with(Action::MoveRight), 60), 60);
+        sim.restart();
+        assert_eq!(sim.get_character().get_pos(), vec2!(100.0, 86.0));
+    }
+
+    #[test]
+    fn falls_onto_the_first_platform_from_spawn() {
+        let mut sim = Simulation::new(test_level(), MovementProfile::default());
+        sim.run(&mut ScriptedInput::default(), seconds(4));
+
+        assert_eq!(sim.get_character().get_state(), PlayerState::Grounded);
+        assert_eq!(standing_on(&sim), Some(0));
+    }
+
+    #[test]
+    fn holding_right_runs_off_onto_the_floor() {
+        let mut sim = Simulation::new(test_level(), MovementProfile::default());
+        let right = ActionSet::empty().with(Action::MoveRight);
+        sim.run(&mut ScriptedInput::default(), seconds(4));
+        // a short run is enough to slide off the first platform but not to clear the floor
+        sim.run(&mut ScriptedInput::default().hold(right, 150), seconds(8));
+
+        assert_eq!(sim.get_character().get_state(), PlayerState::Grounded);
+        assert_eq!(standing_on(&sim), Some(1));
+    }
+
+    #[test]
+    fn running_into_the_goal_finishes() {
+        let mut level = test_level();
+        // tucked under the first platform so it takes a trip back left along the floor
+        level.add_entity(Entity::new(EntityKind::Goal, vec2!(0.0, 480.0), vec2!(80.0, 120.0)));
+        let mut sim = Simulation::new(level, MovementProfile::default());
+        let right = ActionSet::empty().with(Action::MoveRight);
+        sim.run(&mut ScriptedInput::default(), seconds(4));
+        sim.run(&mut ScriptedInput::default().hold(right, 150), seconds(4));
+        assert!(!sim.reached_goal());
+
+        let left = ActionSet::empty().with(Action::MoveLeft);
+        let mut reached = false;
+        for _ in 0..seconds(4) {
+            sim.step(left);
+            reached |= sim.reached_goal();
+        }
+
+        assert!(reached);
+    }
+
+    #[test]
+    fn falling_out_of_the_level_respawns_at_the_start() {
+        let mut sim = Simulation::new(test_level(), MovementProfile::default());
+        let right = ActionSet::empty().with(Action::MoveRight);
+
+        let mut died_at = None;
+        for _ in 0..seconds(10) {
+            sim.step(right);
+            if sim.get_events().contains(&SimEvent::Died) {
+                died_at = Some(sim.get_ticks());
+                break;
+            }
+        }
+        let died_at = died_at.expect("should have fallen out");
+        assert_eq!(sim.get_character().get_state(), PlayerState::Dead);
+
+        // frozen until the delay is up
+        let dead_pos = sim.get_character().get_pos();
+        sim.run(&mut ScriptedInput::default().hold(right, 10), 10);
+        assert_eq!(sim.get_character().get_pos(), dead_pos);
+
+        while !sim.get_events().contains(&SimEvent::Respawned) {
+            sim.step(ActionSet::empty());
+        }
+        assert_eq!(sim.get_ticks() - died_at, (RESPAWN_DELAY * TICK_RATE as f32) as u64);
+        assert_eq!(sim.get_character().get_pos(), vec2!(0.0, -200.0));
+    }
+
+    #[test]
+    fn spikes_kill_on_touch() {
+        let mut level = test_level();
+        level.add_entity(Entity::new(EntityKind::Spikes, vec2!(10.0, 180.0), vec2!(300.0, 20.0)));
+        let mut sim = Simulation::new(level, MovementProfile::default());
+
+        let mut died = false;
+        for _ in 0..seconds(4) {
+            sim.step(ActionSet::empty());
+            died |= sim.get_events().contains(&SimEvent::Died);
+        }
+
+        assert!(died);
+        assert_eq!(standing_on(&sim), None);
+    }
+
+    #[test]
+    fn checkpoints_survive_death_but_not_restart() {
+        let mut level = test_level();
+        // on the floor where running right off the first platform lands, then a pit past it
+        level.add_entity(Entity::new(EntityKind::Checkpoint, vec2!(300.0, 450.0), vec2!(300.0, 150.0)));
+        let mut sim = Simulation::new(level, MovementProfile::default());
+        let right = ActionSet::empty().with(Action::MoveRight);
+
+        sim.run(&mut ScriptedInput::default(), seconds(4));
+        let mut script = ScriptedInput::default().hold(right, 150);
+        let mut reached = None;
+        while reached.is_none() && sim.get_ticks() < seconds(12) {
+            sim.step(script.next_tick());
+            reached = sim.get_events().iter().find_map(|e| match e {
+                SimEvent::CheckpointReached(idx) => Some(*idx),
+                _ => None,
+            });
+        }
+        assert_eq!(reached, Some(0));
+
+        // keep going right off the end of the floor to die
+        while !sim.get_events().contains(&SimEvent::Respawned) {
+            sim.step(right);
+        }
+        assert_eq!(sim.get_active_checkpoints(), &[0]);
+        assert_eq!(sim.get_character().get_pos(), vec2!(300.0, 600.0 - sim.get_character().get_size().y));
+
+        sim.restart();
+        assert!(sim.get_active_checkpoints().is_empty());
+    }
+
+    #[test]
+    fn jumping_leaves_the_ground_and_comes_back() {
+        let mut sim = Simulation::new(test_level(), MovementProfile::default());
+        sim.run(&mut ScriptedInput::default(), seconds(4));
+        let ground_y = sim.get_character().get_pos().y;
+
+        sim.step(ActionSet::empty().with(Action::Jump));
+        sim.step(ActionSet::empty().with(Action::Jump));
+        assert_eq!(sim.get_character().get_state(), PlayerState::Jumping);
+        assert!(sim.get_character().get_pos().y < ground_y);
+
+        sim.run(&mut ScriptedInput::default(), seconds(5));
+        assert_eq!(sim.get_character().get_state(), PlayerState::Grounded);
+        assert_eq!(sim.get_character().get_pos().y, ground_y);
+    }
+
+    // ticks from spawn until the player first stands on something without touching anything
+    fn landing_tick() -> u64 {
+        let mut sim = Simulation::new(test_level(), MovementProfile::default());
+        while sim.get_character().get_state() != PlayerState::Grounded {
+            sim.step(ActionSet::empty());
+        }
+        sim.get_ticks()
+    }
+
+    // highest the player gets over `ticks` ticks, smaller y is higher
+    fn peak_y(sim: &mut Simulation, script: &mut ScriptedInput, ticks: u64) -> f32 {
+        let mut peak = sim.get_character().get_pos().y;
+        for _ in 0..ticks {
+            sim.step(script.next_tick());
+            peak = peak.min(sim.get_character().get_pos().y);
+        }
+        peak
+    }
+
+    #[test]
+    fn jumping_just_after_running_off_a_ledge() {
+        let right = ActionSet::empty().with(Action::MoveRight);
+        let coyote_ticks = (MovementProfile::default().jump_assist.coyote_time * TICK_RATE as f32) as u64;
+
+        for (late_by, should_jump) in [(coyote_ticks / 2, true), (coyote_ticks + 2, false)] {
+            let mut sim = Simulation::new(test_level(), MovementProfile::default());
+            sim.run(&mut ScriptedInput::default(), seconds(4));
+            while sim.get_character().get_state() != PlayerState::Falling {
+                sim.step(right);
+            }
+
+            sim.run(&mut ScriptedInput::default().hold(right, late_by as usize), late_by);
+            sim.step(right.with(Action::Jump));
+
+            let jumped = sim.get_character().get_state() == PlayerState::Jumping;
+            assert_eq!(jumped, should_jump, "jumping {} ticks after the ledge", late_by);
+        }
+    }
+
+    #[test]
+    fn jump_pressed_before_landing_fires_on_landing() {
+        let landed_at = landing_tick();
+        let jump = ActionSet::empty().with(Action::Jump);
+
+        let mut sim = Simulation::new(test_level(), MovementProfile::default());
+        sim.run(&mut ScriptedInput::default(), landed_at - 5);
+        let mut script = ScriptedInput::default().hold(jump, 10);
+        let mut jumped = false;
+        for _ in 0..10 {
+            sim.step(script.next_tick());
+            jumped |= sim.get_character().get_state() == PlayerState::Jumping;
+        }
+
+        assert!(jumped);
+    }
+
+    #[test]
+    fn buffered_jump_let_go_before_landing_is_cut() {
+        let landed_at = landing_tick();
+        let jump = ActionSet::empty().with(Action::Jump);
+
+        // a full jump held from the ground
+        let mut held = Simulation::new(test_level(), MovementProfile::default());
+        held.run(&mut ScriptedInput::default(), seconds(4));
+        let ground_y = held.get_character().get_pos().y;
+        let held_peak = peak_y(&mut held, &mut ScriptedInput::default().hold(jump, 120), 120);
+
+        // tapped a few ticks before landing and let go before it fires
+        let mut tapped = Simulation::new(test_level(), MovementProfile::default());
+        tapped.run(&mut ScriptedInput::default(), landed_at - 3);
+        let mut script = ScriptedInput::default().hold(jump, 1);
+        tapped.run(&mut script, 1);
+        while tapped.get_character().get_state() != PlayerState::Jumping {
+            assert!(tapped.get_ticks() < landed_at + 5, "buffered jump never fired");
+            tapped.step(ActionSet::empty());
+        }
+        let tapped_peak = peak_y(&mut tapped, &mut ScriptedInput::default(), 120);
+
+        assert!(tapped_peak < ground_y);
+        assert!(tapped_peak > held_peak, "tap peaked at {} but a held jump at {}", tapped_peak, held_peak);
+    }
+
+    #[test]
+    fn landing_mid_dash_doesnt_refill_charges() {
+        let dash_down = ActionSet::empty().with(Action::Dash).with(Action::MoveDown);
+
+        let mut sim = Simulation::new(test_level(), MovementProfile::default());
+        sim.run(&mut ScriptedInput::default(), landing_tick() - 2);
+        sim.step(dash_down);
+        assert_eq!(sim.get_character().get_state(), PlayerState::Dashing);
+        assert_eq!(sim.get_character().get_dash_charges(), 0);
+
+        while sim.get_character().get_state() == PlayerState::Dashing {
+            sim.step(ActionSet::empty());
+            if sim.get_character().get_contacts().bottom && sim.get_character().get_state() == PlayerState::Dashing {
+                assert_eq!(sim.get_character().get_dash_charges(), 0);
+            }
+        }
+
+        assert_eq!(sim.get_character().get_state(), PlayerState::Grounded);
+        assert_eq!(sim.get_character().get_dash_charges(), MovementProfile::default().dash.air_charges);
+    }
+
+    #[test]
+    fn ending_a_dash_only_caps_sideways_speed() {
+        let profile = MovementProfile::default();
+        let dash = ActionSet::empty().with(Action::Dash).with(Action::MoveRight).with(Action::MoveDown);
+
+        let mut sim = Simulation::new(test_level(), profile);
+        sim.step(dash);
+        while sim.get_character().get_state() == PlayerState::Dashing {
+            sim.step(ActionSet::empty());
+        }
+
+        let speed = sim.get_character().get_speed();
+        assert_eq!(sim.get_character().get_state(), PlayerState::Falling);
+        assert_eq!(speed.x, profile.max_air_speed);
+        assert!((speed.y - profile.dash.speed * std::f32::consts::FRAC_1_SQRT_2).abs() < 0.01, "left the dash falling at {}", speed.y);
+    }
+
+    #[test]
+    fn same_input_gives_the_same_run() {
+        let right = ActionSet::empty().with(Action::MoveRight);
+        let script = ScriptedInput::default()
+            .hold(right, 200)
+            .hold(right.with(Action::Jump), 30)
+            .hold(ActionSet::empty().with(Action::Dash), 5)
+            .hold(right, 300);
+
+        let mut a = Simulation::new(test_level(), MovementProfile::default());
+        let mut b = Simulation::new(test_level(), MovementProfile::default());
+        a.run(&mut script.clone(), script.len() as u64);
+        b.run(&mut script.clone(), script.len() as u64);
+
+        assert_eq!(a.get_character().get_pos(), b.get_character().get_pos());
+        assert_eq!(a.get_character().get_speed(), b.get_character().get_speed());
+        assert_eq!(a.get_ticks(), script.len() as u64);
+    }
+}
@@ -0,0 +1,40 @@
+use bottomless_pit::colour::Colour;
+use bottomless_pit::engine_handle::Engine;
+use bottomless_pit::material::{Material, MaterialBuilder};
+use bottomless_pit::render::RenderInformation;
+use bottomless_pit::texture::{SamplerType, Texture};
+use bottomless_pit::vectors::Vec2;
+
+use crate::character::PLAYER_SIZE;
+
+/// the players texture, kept apart from `Character` so the physics dont need a GPU
+pub struct PlayerSprite {
+    material: Material,
+}
+
+impl PlayerSprite {
+    pub fn new(engine: &mut Engine) -> Self {
+        let texture = Texture::new_with_sampler(engine, "speed-game/assets/shork.png", SamplerType::NearestNeighbor);
+
+        let material = MaterialBuilder::new()
+            .add_texture(texture)
+            .build(engine);
+
+        Self {
+            material,
+        }
+    }
+
+    /// draws the player at `pos` with the ghost of a previous run behind it if there is one,
+    /// a dead player is tinted red until they respawn
+    pub fn draw<'p, 'o>(&'o mut self, renderer: &mut RenderInformation<'p, 'o>, pos: Vec2<f32>, ghost: Option<Vec2<f32>>, dead: bool) where 'o: 'p {
+        if let Some(ghost) = ghost {
+            // see through like the editors move preview
+            self.material.add_rectangle(ghost, PLAYER_SIZE, Colour::from_rgba(255.0, 255.0, 255.0, 0.5), renderer);
+        }
+        let tint = if dead { Colour::RED } else { Colour::WHITE };
+        self.material.add_rectangle(pos, PLAYER_SIZE, tint, renderer);
+
+        self.material.draw(renderer);
+    }
+}
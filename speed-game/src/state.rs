@@ -0,0 +1,291 @@
+use std::fmt::Display;
+
+use bottomless_pit::vectors::Vec2;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum PlayerState {
+    Grounded,
+    Jumping,
+    Falling,
+    WallSliding,
+    Dashing,
+    /// hit a hazard and waiting to respawn, nothing leaves this, respawning makes a new player
+    Dead,
+}
+
+impl PlayerState {
+    #[cfg(test)]
+    pub const ALL: [Self; 6] = [
+        Self::Grounded,
+        Self::Jumping,
+        Self::Falling,
+        Self::WallSliding,
+        Self::Dashing,
+        Self::Dead,
+    ];
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum TransReason {
+    GroudCollision,
+    JumpStart,
+    NothingBellow,
+    CeilingCollision,
+    WallCollision,
+    WallRelease,
+    WallJump,
+    DashStart,
+    DashEnd,
+    Killed,
+}
+
+impl TransReason {
+    #[cfg(test)]
+    pub const ALL: [Self; 10] = [
+        Self::GroudCollision,
+        Self::JumpStart,
+        Self::NothingBellow,
+        Self::CeilingCollision,
+        Self::WallCollision,
+        Self::WallRelease,
+        Self::WallJump,
+        Self::DashStart,
+        Self::DashEnd,
+        Self::Killed,
+    ];
+}
+
+/// the parts of the player guards are allowed to look at
+#[derive(Clone, Copy, Debug)]
+pub struct TransitionContext {
+    pub speed: Vec2<f32>,
+}
+
+type Guard = fn(&TransitionContext) -> bool;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Effect {
+    /// move to the requested state
+    Allow,
+    /// an expected request that is deliberately turned down
+    Ignore,
+}
+
+struct Rule {
+    from: PlayerState,
+    to: PlayerState,
+    // None matches any reason
+    reason: Option<TransReason>,
+    guard: Option<Guard>,
+    effect: Effect,
+}
+
+const fn allow(from: PlayerState, to: PlayerState, reason: TransReason) -> Rule {
+    Rule { from, to, reason: Some(reason), guard: None, effect: Effect::Allow }
+}
+
+const fn allow_if(from: PlayerState, to: PlayerState, reason: TransReason, guard: Guard) -> Rule {
+    Rule { from, to, reason: Some(reason), guard: Some(guard), effect: Effect::Allow }
+}
+
+const fn ignore(from: PlayerState, to: PlayerState, reason: Option<TransReason>) -> Rule {
+    Rule { from, to, reason, guard: None, effect: Effect::Ignore }
+}
+
+fn moving_down(ctx: &TransitionContext) -> bool {
+    ctx.speed.y > 0.0
+}
+
+// dont grab a wall while still going up
+fn not_rising(ctx: &TransitionContext) -> bool {
+    ctx.speed.y >= 0.0
+}
+
+use PlayerState as S;
+use TransReason as R;
+
+/// every transition the player can make. the first matching rule wins and anything
+/// not listed here is rejected. asking to stay in the current state is always fine
+const RULES: &[Rule] = &[
+    allow(S::Grounded, S::Jumping, R::JumpStart),
+    allow(S::Grounded, S::Falling, R::NothingBellow),
+    allow(S::Grounded, S::Dashing, R::DashStart),
+
+    allow_if(S::Jumping, S::Falling, R::NothingBellow, moving_down),
+    allow(S::Jumping, S::Falling, R::CeilingCollision),
+    allow(S::Jumping, S::Grounded, R::GroudCollision),
+    allow_if(S::Jumping, S::WallSliding, R::WallCollision, not_rising),
+    allow(S::Jumping, S::Dashing, R::DashStart),
+
+    // only requested inside the coyote window
+    allow(S::Falling, S::Jumping, R::JumpStart),
+    allow(S::Falling, S::Grounded, R::GroudCollision),
+    allow(S::Falling, S::WallSliding, R::WallCollision),
+    allow(S::Falling, S::Dashing, R::DashStart),
+
+    allow(S::WallSliding, S::Falling, R::WallRelease),
+    allow(S::WallSliding, S::Grounded, R::GroudCollision),
+    allow(S::WallSliding, S::Jumping, R::WallJump),
+    allow(S::WallSliding, S::Dashing, R::DashStart),
+    // sliding is already in the air so having nothing bellow changes nothing
+    ignore(S::WallSliding, S::Falling, Some(R::NothingBellow)),
+
+    allow(S::Dashing, S::Grounded, R::DashEnd),
+    allow(S::Dashing, S::Falling, R::DashEnd),
+    // the dash carries on until its timer runs out no matter what it touches
+    ignore(S::Dashing, S::Grounded, None),
+    ignore(S::Dashing, S::Falling, None),
+    ignore(S::Dashing, S::Jumping, None),
+    ignore(S::Dashing, S::WallSliding, None),
+
+    allow(S::Grounded, S::Dead, R::Killed),
+    allow(S::Jumping, S::Dead, R::Killed),
+    allow(S::Falling, S::Dead, R::Killed),
+    allow(S::WallSliding, S::Dead, R::Killed),
+    // a dash doesnt make you safe from hazards
+    allow(S::Dashing, S::Dead, R::Killed),
+];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TransitionError {
+    /// the table has no rule for this, which means a bug somewhere
+    NotAllowed {
+        from: PlayerState,
+        to: PlayerState,
+        reason: TransReason,
+    },
+    /// there is a rule but its guard said no this time
+    GuardFailed,
+    /// the table says to ignore this request
+    Ignored,
+}
+
+impl Display for TransitionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NotAllowed { from, to, reason } => write!(f, "no transition from {:?} to {:?} because of {:?}", from, to, reason),
+            Self::GuardFailed => write!(f, "transition guard failed"),
+            Self::Ignored => write!(f, "transition ignored"),
+        }
+    }
+}
+
+impl std::error::Error for TransitionError {}
+
+/// looks up what state the player should end up in
+pub fn check_transition(from: PlayerState, to: PlayerState, reason: TransReason, ctx: &TransitionContext) -> Result<PlayerState, TransitionError> {
+    if from == to {
+        return Ok(from);
+    }
+
+    let rule = RULES
+        .iter()
+        .find(|r| r.from == from && r.to == to && r.reason.is_none_or(|rr| rr == reason))
+        .ok_or(TransitionError::NotAllowed { from, to, reason })?;
+
+    if rule.effect == Effect::Ignore {
+        return Err(TransitionError::Ignored);
+    }
+
+    match rule.guard {
+        Some(guard) if !guard(ctx) => Err(TransitionError::GuardFailed),
+        _ => Ok(to),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bottomless_pit::vec2;
+
+    // every guard passes with this
+    const FALLING: TransitionContext = TransitionContext { speed: Vec2 { x: 0.0, y: 10.0 } };
+
+    const EXPECTED: &[(PlayerState, PlayerState, TransReason)] = &[
+        (S::Grounded, S::Jumping, R::JumpStart),
+        (S::Grounded, S::Falling, R::NothingBellow),
+        (S::Grounded, S::Dashing, R::DashStart),
+        (S::Jumping, S::Falling, R::NothingBellow),
+        (S::Jumping, S::Falling, R::CeilingCollision),
+        (S::Jumping, S::Grounded, R::GroudCollision),
+        (S::Jumping, S::WallSliding, R::WallCollision),
+        (S::Jumping, S::Dashing, R::DashStart),
+        (S::Falling, S::Jumping, R::JumpStart),
+        (S::Falling, S::Grounded, R::GroudCollision),
+        (S::Falling, S::WallSliding, R::WallCollision),
+        (S::Falling, S::Dashing, R::DashStart),
+        (S::WallSliding, S::Falling, R::WallRelease),
+        (S::WallSliding, S::Grounded, R::GroudCollision),
+        (S::WallSliding, S::Jumping, R::WallJump),
+        (S::WallSliding, S::Dashing, R::DashStart),
+        (S::Dashing, S::Grounded, R::DashEnd),
+        (S::Dashing, S::Falling, R::DashEnd),
+        (S::Grounded, S::Dead, R::Killed),
+        (S::Jumping, S::Dead, R::Killed),
+        (S::Falling, S::Dead, R::Killed),
+        (S::WallSliding, S::Dead, R::Killed),
+        (S::Dashing, S::Dead, R::Killed),
+    ];
+
+    #[test]
+    fn every_pair_has_a_defined_outcome() {
+        for from in PlayerState::ALL {
+            for to in PlayerState::ALL {
+                for reason in TransReason::ALL {
+                    let result = check_transition(from, to, reason, &FALLING);
+
+                    if from == to {
+                        assert_eq!(result, Ok(from), "{:?} -> {:?} ({:?})", from, to, reason);
+                    } else if EXPECTED.contains(&(from, to, reason)) {
+                        assert_eq!(result, Ok(to), "{:?} -> {:?} ({:?})", from, to, reason);
+                    } else {
+                        assert!(result.is_err(), "{:?} -> {:?} ({:?}) should be rejected", from, to, reason);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn ignored_requests_are_not_errors_in_the_table() {
+        for reason in TransReason::ALL {
+            if reason != R::DashEnd {
+                assert_eq!(check_transition(S::Dashing, S::Grounded, reason, &FALLING), Err(TransitionError::Ignored));
+            }
+        }
+
+        assert_eq!(check_transition(S::WallSliding, S::Falling, R::NothingBellow, &FALLING), Err(TransitionError::Ignored));
+    }
+
+    #[test]
+    fn unknown_requests_are_reported() {
+        let result = check_transition(S::Grounded, S::WallSliding, R::JumpStart, &FALLING);
+
+        assert_eq!(result, Err(TransitionError::NotAllowed { from: S::Grounded, to: S::WallSliding, reason: R::JumpStart }));
+    }
+
+    #[test]
+    fn guards_block_transitions_while_rising() {
+        let rising = TransitionContext { speed: vec2!(0.0, -10.0) };
+
+        assert_eq!(check_transition(S::Jumping, S::Falling, R::NothingBellow, &rising), Err(TransitionError::GuardFailed));
+        assert_eq!(check_transition(S::Jumping, S::WallSliding, R::WallCollision, &rising), Err(TransitionError::GuardFailed));
+        // the ceiling ends a jump no matter the speed
+        assert_eq!(check_transition(S::Jumping, S::Falling, R::CeilingCollision, &rising), Ok(S::Falling));
+    }
+
+    #[test]
+    fn no_duplicate_rules() {
+        for (i, a) in RULES.iter().enumerate() {
+            for b in &RULES[i + 1..] {
+                let same_reason = a.reason.is_none() || b.reason.is_none() || a.reason == b.reason;
+                // a specific rule in front of a catch all is how DashEnd is carved out
+                let shadowed_catch_all = a.reason.is_some() && b.reason.is_none();
+
+                assert!(!(a.from == b.from && a.to == b.to && same_reason) || shadowed_catch_all);
+            }
+        }
+    }
+}
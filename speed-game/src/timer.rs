@@ -0,0 +1,404 @@
+use std::fmt::Display;
+use std::path::{Path, PathBuf};
+
+use utils::config::{parse_entries, ConfigError};
+
+use crate::input::ActionSet;
+use crate::timestep::FIXED_DT;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TimerState {
+    /// spawned but nothing pressed yet
+    Waiting,
+    Running,
+    Finished,
+}
+
+/// times a run in whole ticks so it matches replays exactly
+#[derive(Clone, Debug)]
+pub struct SpeedrunTimer {
+    state: TimerState,
+    ticks: u64,
+    // each checkpoint reached in the order they were touched
+    splits: Vec<Split>,
+}
+
+impl SpeedrunTimer {
+    pub fn new() -> Self {
+        Self {
+            state: TimerState::Waiting,
+            ticks: 0,
+            splits: Vec::new(),
+        }
+    }
+
+    /// call every tick with that ticks input, the clock starts on the first gameplay input
+    pub fn tick(&mut self, held: ActionSet) {
+        if self.state == TimerState::Waiting && held.has_gameplay() {
+            self.state = TimerState::Running;
+        }
+
+        if self.state == TimerState::Running {
+            self.ticks += 1;
+        }
+    }
+
+    /// marks reaching the checkpoint at entity index `checkpoint`, returns which split it was
+    pub fn split(&mut self, checkpoint: usize) -> Option<usize> {
+        if self.state != TimerState::Running {
+            return None;
+        }
+
+        self.splits.push(Split { checkpoint, at: self.ticks });
+        Some(self.splits.len() - 1)
+    }
+
+    /// stops the clock at the level exit and hands back the finished run
+    pub fn finish(&mut self) -> Option<RunTimes> {
+        if self.state != TimerState::Running {
+            return None;
+        }
+
+        self.state = TimerState::Finished;
+        Some(RunTimes {
+            total: self.ticks,
+            splits: self.splits.clone(),
+        })
+    }
+
+    pub fn reset(&mut self) {
+        *self = Self::new();
+    }
+
+    pub fn get_state(&self) -> TimerState {
+        self.state
+    }
+
+    pub fn get_ticks(&self) -> u64 {
+        self.ticks
+    }
+
+    pub fn get_splits(&self) -> &[Split] {
+        &self.splits
+    }
+
+    pub fn elapsed_secs(&self) -> f32 {
+        ticks_to_secs(self.ticks)
+    }
+}
+
+impl Default for SpeedrunTimer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+pub fn ticks_to_secs(ticks: u64) -> f32 {
+    ticks as f32 * FIXED_DT
+}
+
+/// `m:ss.cc`, minutes are left off under a minute
+pub fn format_time(ticks: u64) -> String {
+    format_secs(ticks_to_secs(ticks))
+}
+
+/// same as `format_time` for times that arent in ticks, like par times
+pub fn format_secs(secs: f32) -> String {
+    let minutes = (secs / 60.0) as u64;
+    let rest = secs - minutes as f32 * 60.0;
+
+    if minutes == 0 {
+        format!("{:.2}", rest)
+    } else {
+        format!("{}:{:05.2}", minutes, rest)
+    }
+}
+
+/// a checkpoint reached during a run
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Split {
+    /// the checkpoints index in the levels entities
+    pub checkpoint: usize,
+    /// ticks from the start
+    pub at: u64,
+}
+
+/// a finished run, all in ticks from the start
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct RunTimes {
+    pub total: u64,
+    pub splits: Vec<Split>,
+}
+
+/// how a split compares to the personal best
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SplitResult {
+    /// quickest this segment has ever been done
+    Gold(f32),
+    Ahead(f32),
+    Behind(f32),
+    /// nothing to compare against yet
+    New,
+}
+
+/// the best complete run on a level plus the best time for every segment on its own.
+/// segments are keyed by the checkpoint they end at so taking checkpoints in a
+/// different order compares like with like
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PersonalBest {
+    pub run: Option<RunTimes>,
+    /// (checkpoint entity index, quickest ticks into it from the split before)
+    pub best_segments: Vec<(usize, u64)>,
+    /// quickest ticks from the last checkpoint, or the start, to the goal
+    pub best_final_segment: Option<u64>,
+}
+
+impl PersonalBest {
+    /// stored one file per level named after its hash, so editing a level starts it fresh
+    pub fn path_for(dir: &Path, level_hash: u64) -> PathBuf {
+        dir.join(format!("{:016x}.pb", level_hash))
+    }
+
+    /// the saved bests for a level or empty ones if there arent any yet
+    pub fn load_or_default(dir: &Path, level_hash: u64) -> Self {
+        let path = Self::path_for(dir, level_hash);
+        match std::fs::read_to_string(&path) {
+            Ok(text) => Self::parse(&text).unwrap_or_else(|e| {
+                eprintln!("ignoring personal best {}: {}", path.display(), e);
+                Self::default()
+            }),
+            Err(_) => Self::default(),
+        }
+    }
+
+    pub fn save(&self, dir: &Path, level_hash: u64) -> Result<(), std::io::Error> {
+        std::fs::create_dir_all(dir)?;
+        std::fs::write(Self::path_for(dir, level_hash), self.to_string())
+    }
+
+    pub fn parse(text: &str) -> Result<Self, ConfigError> {
+        let mut pb = Self::default();
+        let mut total = None;
+        let mut splits = Vec::new();
+
+        for entry in parse_entries(text)? {
+            let numbers = entry.value
+                .split(',')
+                .map(|n| n.trim().parse::<u64>())
+                .collect::<Result<Vec<u64>, _>>()
+                .map_err(|_| ConfigError::new(entry.line, format!("`{}` expects tick counts", entry.key)))?;
+
+            match (entry.key, numbers.as_slice()) {
+                ("total", [ticks]) => total = Some(*ticks),
+                ("best_final_segment", [ticks]) => pb.best_final_segment = Some(*ticks),
+                ("split", [checkpoint, at]) => splits.push(Split { checkpoint: *checkpoint as usize, at: *at }),
+                ("best_segment", [checkpoint, ticks]) => pb.best_segments.push((*checkpoint as usize, *ticks)),
+                // older files kept splits in the order they were touched with no checkpoint,
+                // they cant be matched up so only the total is kept
+                ("split" | "best_segment", [_]) => {},
+                ("total" | "best_final_segment" | "split" | "best_segment", _) => {
+                    return Err(ConfigError::new(entry.line, format!("wrong number of values for `{}`", entry.key)));
+                },
+                _ => return Err(entry.unknown_key()),
+            }
+        }
+
+        pb.run = total.map(|total| RunTimes { total, splits });
+        Ok(pb)
+    }
+
+    /// quickest anyone has got into `checkpoint` from the split before it
+    pub fn get_best_segment(&self, checkpoint: usize) -> Option<u64> {
+        self.best_segments
+            .iter()
+            .find(|(c, _)| *c == checkpoint)
+            .map(|(_, ticks)| *ticks)
+    }
+
+    /// compares the split that was just made against the best run and best segment
+    /// for the same checkpoint
+    pub fn compare_split(&self, index: usize, splits: &[Split]) -> SplitResult {
+        let Some(split) = splits.get(index) else {
+            return SplitResult::New;
+        };
+        let segment = split.at - index.checked_sub(1).map_or(0, |i| splits[i].at);
+
+        let best_at = self.run
+            .as_ref()
+            .and_then(|r| r.splits.iter().find(|s| s.checkpoint == split.checkpoint));
+        let Some(best_at) = best_at.map(|s| s.at) else {
+            return SplitResult::New;
+        };
+        let delta = ticks_to_secs(split.at) - ticks_to_secs(best_at);
+
+        match self.get_best_segment(split.checkpoint) {
+            Some(best) if segment < best => SplitResult::Gold(delta),
+            _ if split.at <= best_at => SplitResult::Ahead(delta),
+            _ => SplitResult::Behind(delta),
+        }
+    }
+
+    /// folds a finished run in, returns true if it was a new best overall
+    pub fn record(&mut self, run: &RunTimes) -> bool {
+        let mut last = 0;
+        for split in &run.splits {
+            let segment = split.at - last;
+            last = split.at;
+
+            match self.best_segments.iter_mut().find(|(c, _)| *c == split.checkpoint) {
+                Some((_, best)) => *best = (*best).min(segment),
+                None => self.best_segments.push((split.checkpoint, segment)),
+            }
+        }
+
+        let final_segment = run.total - last;
+        self.best_final_segment = Some(self.best_final_segment.map_or(final_segment, |best| best.min(final_segment)));
+
+        let is_best = self.run.as_ref().is_none_or(|best| run.total < best.total);
+        if is_best {
+            self.run = Some(run.clone());
+        }
+
+        is_best
+    }
+}
+
+/// writes the bests back out in the same format `parse` reads
+impl Display for PersonalBest {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "# all times are in ticks, splits and segments are `checkpoint, ticks`")?;
+        if let Some(run) = &self.run {
+            writeln!(f, "total = {}", run.total)?;
+            for split in &run.splits {
+                writeln!(f, "split = {}, {}", split.checkpoint, split.at)?;
+            }
+        }
+
+        for (checkpoint, ticks) in &self.best_segments {
+            writeln!(f, "best_segment = {}, {}", checkpoint, ticks)?;
+        }
+        if let Some(ticks) = self.best_final_segment {
+            writeln!(f, "best_final_segment = {}", ticks)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::Action;
+
+    // `splits` are (checkpoint, tick) pairs
+    fn timed_run(wait: u64, splits: &[(usize, u64)], total: u64) -> RunTimes {
+        let mut timer = SpeedrunTimer::new();
+        for _ in 0..wait {
+            timer.tick(ActionSet::empty());
+        }
+
+        let right = ActionSet::empty().with(Action::MoveRight);
+        for tick in 1..=total {
+            timer.tick(right);
+            for (checkpoint, _) in splits.iter().filter(|(_, at)| *at == tick) {
+                timer.split(*checkpoint);
+            }
+        }
+
+        timer.finish().unwrap()
+    }
+
+    fn splits(pairs: &[(usize, u64)]) -> Vec<Split> {
+        pairs.iter().map(|(checkpoint, at)| Split { checkpoint: *checkpoint, at: *at }).collect()
+    }
+
+    #[test]
+    fn starts_on_first_gameplay_input() {
+        let mut timer = SpeedrunTimer::new();
+        timer.tick(ActionSet::empty().with(Action::ToggleDebug));
+        timer.tick(ActionSet::empty());
+        assert_eq!(timer.get_state(), TimerState::Waiting);
+        assert_eq!(timer.split(0), None);
+
+        let run = timed_run(50, &[(3, 10), (1, 20)], 30);
+        assert_eq!(run, RunTimes { total: 30, splits: splits(&[(3, 10), (1, 20)]) });
+    }
+
+    #[test]
+    fn finished_timer_stops() {
+        let mut timer = SpeedrunTimer::new();
+        timer.tick(ActionSet::empty().with(Action::Jump));
+        timer.finish();
+        timer.tick(ActionSet::empty().with(Action::Jump));
+
+        assert_eq!(timer.get_ticks(), 1);
+        assert_eq!(timer.finish(), None);
+    }
+
+    #[test]
+    fn gold_ahead_and_behind() {
+        let mut pb = PersonalBest::default();
+        assert!(pb.record(&timed_run(0, &[(0, 100), (1, 200)], 300)));
+
+        // quicker first segment than ever
+        assert!(matches!(pb.compare_split(0, &splits(&[(0, 90)])), SplitResult::Gold(_)));
+        // second segment of 110 is slower than the best 100 but the total is still ahead
+        assert!(matches!(pb.compare_split(1, &splits(&[(0, 90), (1, 200)])), SplitResult::Ahead(d) if d == 0.0));
+        assert!(matches!(pb.compare_split(1, &splits(&[(0, 90), (1, 240)])), SplitResult::Behind(_)));
+        // a checkpoint the best run never touched
+        assert_eq!(pb.compare_split(2, &splits(&[(0, 90), (1, 200), (5, 250)])), SplitResult::New);
+    }
+
+    #[test]
+    fn splits_compare_by_checkpoint_not_touch_order() {
+        let mut pb = PersonalBest::default();
+        pb.record(&timed_run(0, &[(0, 100), (1, 200)], 300));
+
+        // same checkpoints the other way round, checkpoint 1 first at 150 is ahead of
+        // the best runs 200 even though the best runs first split was at 100
+        let other_way = splits(&[(1, 150)]);
+        assert!(matches!(pb.compare_split(0, &other_way), SplitResult::Ahead(d) if d < 0.0));
+
+        let other_way = splits(&[(1, 150), (0, 260)]);
+        assert!(matches!(pb.compare_split(1, &other_way), SplitResult::Behind(d) if d > 0.0));
+    }
+
+    #[test]
+    fn slower_runs_still_improve_segments() {
+        let mut pb = PersonalBest::default();
+        pb.record(&timed_run(0, &[(0, 100), (1, 200)], 300));
+
+        assert!(!pb.record(&timed_run(0, &[(0, 80), (1, 250)], 350)));
+        assert_eq!(pb.run.as_ref().unwrap().total, 300);
+        assert_eq!(pb.best_segments, vec![(0, 80), (1, 100)]);
+        assert_eq!(pb.best_final_segment, Some(100));
+
+        // a run with no checkpoints at all only has the final segment
+        pb.record(&timed_run(0, &[], 90));
+        assert_eq!(pb.best_final_segment, Some(90));
+        assert_eq!(pb.run.as_ref().unwrap().total, 90);
+    }
+
+    #[test]
+    fn round_trips_through_text() {
+        let mut pb = PersonalBest::default();
+        pb.record(&timed_run(0, &[(2, 100), (0, 200)], 300));
+
+        assert_eq!(PersonalBest::parse(&pb.to_string()).unwrap(), pb);
+        assert_eq!(PersonalBest::parse("").unwrap(), PersonalBest::default());
+        assert_eq!(PersonalBest::parse("total = soon").unwrap_err().line, 1);
+        assert_eq!(PersonalBest::parse("total = 1\nsplit = 1, 2, 3").unwrap_err().line, 2);
+
+        // splits from before they had checkpoints are dropped but the total is kept
+        let old = PersonalBest::parse("total = 300\nsplit = 100\nbest_segment = 100").unwrap();
+        assert_eq!(old.run, Some(RunTimes { total: 300, splits: vec![] }));
+        assert!(old.best_segments.is_empty());
+    }
+
+    #[test]
+    fn formats_times() {
+        assert_eq!(format_time(0), "0.00");
+        assert_eq!(format_time(180), "1.50");
+        assert_eq!(format_time(120 * 75), "1:15.00");
+    }
+}
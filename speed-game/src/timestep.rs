@@ -0,0 +1,80 @@
+/// the simulation always advances in steps of this many seconds no matter the frame rate
+pub const TICK_RATE: u32 = 120;
+pub const FIXED_DT: f32 = 1.0 / TICK_RATE as f32;
+
+// if a frame takes longer than this many ticks we drop the rest instead of
+// trying to catch up forever
+const MAX_TICKS_PER_FRAME: u32 = 8;
+
+/// turns variable frame times into a whole number of fixed ticks, keeping
+/// whatever is left over for the next frame
+#[derive(Debug)]
+pub struct FixedTimestep {
+    accumulator: f32,
+    step: f32,
+}
+
+impl FixedTimestep {
+    pub fn new(step: f32) -> Self {
+        Self {
+            accumulator: 0.0,
+            step,
+        }
+    }
+
+    /// adds the frames time and returns how many ticks should be simulated
+    pub fn advance(&mut self, frame_dt: f32) -> u32 {
+        self.accumulator += frame_dt;
+
+        let mut ticks = 0;
+        while self.accumulator >= self.step && ticks < MAX_TICKS_PER_FRAME {
+            self.accumulator -= self.step;
+            ticks += 1;
+        }
+
+        if ticks == MAX_TICKS_PER_FRAME {
+            self.accumulator = self.accumulator.min(self.step);
+        }
+
+        ticks
+    }
+
+    /// how far we are between the last tick and the next one, used to smooth out rendering
+    pub fn alpha(&self) -> f32 {
+        (self.accumulator / self.step).clamp(0.0, 1.0)
+    }
+}
+
+impl Default for FixedTimestep {
+    fn default() -> Self {
+        Self::new(FIXED_DT)
+    }
+}
+
+pub fn lerp(start: f32, end: f32, t: f32) -> f32 {
+    start + (end - start) * t
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_remainder_between_frames() {
+        let mut timestep = FixedTimestep::new(0.25);
+
+        assert_eq!(timestep.advance(0.1), 0);
+        assert_eq!(timestep.advance(0.2), 1);
+        assert!((timestep.alpha() - 0.2).abs() < 0.0001);
+        assert_eq!(timestep.advance(0.7), 3);
+    }
+
+    #[test]
+    fn long_frames_are_capped() {
+        let mut timestep = FixedTimestep::new(0.25);
+
+        assert_eq!(timestep.advance(100.0), MAX_TICKS_PER_FRAME);
+        assert!(timestep.alpha() <= 1.0);
+        assert_eq!(timestep.advance(0.0), 1);
+    }
+}
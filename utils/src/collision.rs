@@ -16,6 +16,97 @@ pub fn rect_in_rect(r1_pos: Vec2<f32>, r1_size: Vec2<f32>, r2_pos: Vec2<f32>, r2
         r1_pos.y <= r2_pos.y + r2_size.y
 }
 
+/// like `rect_in_rect` but rectangles that only share an edge dont count as overlapping
+pub fn rect_overlaps(r1_pos: Vec2<f32>, r1_size: Vec2<f32>, r2_pos: Vec2<f32>, r2_size: Vec2<f32>) -> bool {
+    r1_pos.x + r1_size.x > r2_pos.x &&
+        r1_pos.x < r2_pos.x + r2_size.x &&
+        r1_pos.y + r1_size.y > r2_pos.y &&
+        r1_pos.y < r2_pos.y + r2_size.y
+}
+
+/// which side of a rectangle a contact happened on
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Side {
+    Left,
+    Right,
+    Top,
+    Bottom,
+}
+
+/// gaps smaller than this are treated as touching so float error
+/// after a resolution cant make a box slip into what its resting on
+const SWEEP_SKIN: f32 = 0.01;
+
+/// result of sweeping a moving rectangle into a static one
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SweptHit {
+    /// fraction of the movement (0..=1) at which the rectangles first touch
+    pub time: f32,
+    /// surface normal of the static rectangle at the point of contact
+    pub normal: Vec2<f32>,
+}
+
+impl SweptHit {
+    /// the side of the moving rectangle that made contact
+    pub fn side(&self) -> Side {
+        if self.normal.x > 0.0 {
+            Side::Left
+        } else if self.normal.x < 0.0 {
+            Side::Right
+        } else if self.normal.y > 0.0 {
+            Side::Top
+        } else {
+            Side::Bottom
+        }
+    }
+}
+
+/// sweeps the rectangle at `pos` by `delta` against a static rectangle and returns
+/// the earliest time of impact. rectangles that start out overlapping are ignored
+pub fn swept_rect(pos: Vec2<f32>, size: Vec2<f32>, delta: Vec2<f32>, r_pos: Vec2<f32>, r_size: Vec2<f32>) -> Option<SweptHit> {
+    let (x_entry, x_exit) = axis_times(pos.x, size.x, delta.x, r_pos.x, r_size.x)?;
+    let (y_entry, y_exit) = axis_times(pos.y, size.y, delta.y, r_pos.y, r_size.y)?;
+
+    let entry = x_entry.max(y_entry);
+    let exit = x_exit.min(y_exit);
+
+    if entry > exit || !(0.0..=1.0).contains(&entry) {
+        return None;
+    }
+
+    // ties go to the y axis so landing on a corner counts as landing
+    let normal = if x_entry > y_entry {
+        vec2!(-delta.x.signum(), 0.0)
+    } else {
+        vec2!(0.0, -delta.y.signum())
+    };
+
+    Some(SweptHit {
+        time: entry,
+        normal,
+    })
+}
+
+fn axis_times(pos: f32, size: f32, delta: f32, r_pos: f32, r_size: f32) -> Option<(f32, f32)> {
+    if delta == 0.0 {
+        return if pos < r_pos + r_size && pos + size > r_pos {
+            Some((f32::NEG_INFINITY, f32::INFINITY))
+        } else {
+            None
+        };
+    }
+
+    let (gap, far) = if delta > 0.0 {
+        (r_pos - (pos + size), r_pos + r_size - pos)
+    } else {
+        (pos - (r_pos + r_size), pos + size - r_pos)
+    };
+
+    let gap = if gap < 0.0 && gap > -SWEEP_SKIN { 0.0 } else { gap };
+
+    Some((gap / delta.abs(), far / delta.abs()))
+}
+
 pub fn line_line(l1_start: Vec2<f32>, l1_end: Vec2<f32>, l2_start: Vec2<f32>, l2_end: Vec2<f32>) -> bool {
     let ua: f32 = ((l2_end.x-l2_start.x)*(l1_start.y-l2_start.y) - (l2_end.y-l2_start.y)*(l1_start.x-l2_start.x)) / ((l2_end.y-l2_start.y)*(l1_end.x-l1_start.x) - (l2_end.x-l2_start.x)*(l1_end.y-l1_start.y));
     let ub: f32 = ((l1_end.x-l1_start.x)*(l1_start.y-l2_start.y) - (l1_end.y-l1_start.y)*(l1_start.x-l2_start.x)) / ((l2_end.y-l2_start.y)*(l1_end.x-l1_start.x) - (l2_end.x-l2_start.x)*(l1_end.y-l1_start.y));
@@ -29,4 +120,65 @@ pub fn line_in_rect(l_start: Vec2<f32>, l_end: Vec2<f32>, r_pos: Vec2<f32>, r_si
     line_line(l_start, l_end, vec2!(r_pos.x + r_size.x, r_pos.y), vec2!(r_pos.x + r_size.x, r_pos.y + r_size.y)) ||
     line_line(l_start, l_end, r_pos, vec2!(r_pos.x + r_size.x, r_pos.y)) ||
     line_line(l_start, l_end, vec2!(r_pos.x, r_pos.y + r_size.y), vec2!(r_pos.x + r_size.x, r_pos.y + r_size.y))
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shared_edge_is_not_overlap() {
+        assert!(rect_in_rect(vec2!(0.0), vec2!(10.0), vec2!(10.0, 0.0), vec2!(10.0)));
+        assert!(!rect_overlaps(vec2!(0.0), vec2!(10.0), vec2!(10.0, 0.0), vec2!(10.0)));
+    }
+
+    #[test]
+    fn overlapping_rects() {
+        assert!(rect_overlaps(vec2!(0.0), vec2!(10.0), vec2!(5.0), vec2!(10.0)));
+        assert!(!rect_overlaps(vec2!(0.0), vec2!(10.0), vec2!(20.0), vec2!(10.0)));
+    }
+
+    #[test]
+    fn sweep_hits_wall() {
+        let hit = swept_rect(vec2!(0.0), vec2!(10.0), vec2!(20.0, 0.0), vec2!(20.0, 0.0), vec2!(10.0)).unwrap();
+
+        assert_eq!(hit.time, 0.5);
+        assert_eq!(hit.normal, vec2!(-1.0, 0.0));
+        assert_eq!(hit.side(), Side::Right);
+    }
+
+    #[test]
+    fn sweep_lands_on_floor() {
+        let hit = swept_rect(vec2!(0.0), vec2!(10.0), vec2!(5.0, 40.0), vec2!(-50.0, 30.0), vec2!(100.0, 10.0)).unwrap();
+
+        assert_eq!(hit.time, 0.5);
+        assert_eq!(hit.side(), Side::Bottom);
+    }
+
+    #[test]
+    fn sweep_does_not_tunnel_through_thin_rect() {
+        // one pixel thick and moving a thousand pixels in one step
+        let hit = swept_rect(vec2!(0.0), vec2!(10.0), vec2!(0.0, 1000.0), vec2!(0.0, 500.0), vec2!(10.0, 1.0)).unwrap();
+
+        assert_eq!(hit.side(), Side::Bottom);
+        assert!(hit.time > 0.48 && hit.time < 0.5);
+    }
+
+    #[test]
+    fn sweep_misses() {
+        assert!(swept_rect(vec2!(0.0), vec2!(10.0), vec2!(20.0, 0.0), vec2!(0.0, 50.0), vec2!(10.0)).is_none());
+        // moving away
+        assert!(swept_rect(vec2!(0.0), vec2!(10.0), vec2!(-20.0, 0.0), vec2!(20.0, 0.0), vec2!(10.0)).is_none());
+        // too short to reach
+        assert!(swept_rect(vec2!(0.0), vec2!(10.0), vec2!(5.0, 0.0), vec2!(20.0, 0.0), vec2!(10.0)).is_none());
+    }
+
+    #[test]
+    fn sweep_resting_contact() {
+        let hit = swept_rect(vec2!(0.0), vec2!(10.0), vec2!(0.0, 5.0), vec2!(0.0, 10.0), vec2!(10.0)).unwrap();
+        assert_eq!(hit.time, 0.0);
+
+        // sliding along a floor it is resting on is not a hit
+        assert!(swept_rect(vec2!(0.0), vec2!(10.0), vec2!(5.0, 0.0), vec2!(0.0, 10.0), vec2!(100.0, 10.0)).is_none());
+    }
+}
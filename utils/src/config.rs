@@ -0,0 +1,138 @@
+//! tiny `key = value` format used for the games settings files.
+//! blank lines and anything after a `#` are ignored
+
+use std::error::Error;
+use std::fmt::Display;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfigError {
+    /// 1 based line number the error was on, 0 if it wasnt tied to a line
+    pub line: usize,
+    pub message: String,
+}
+
+impl ConfigError {
+    pub fn new(line: usize, message: impl Into<String>) -> Self {
+        Self {
+            line,
+            message: message.into(),
+        }
+    }
+}
+
+impl Display for ConfigError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.line == 0 {
+            write!(f, "{}", self.message)
+        } else {
+            write!(f, "line {}: {}", self.line, self.message)
+        }
+    }
+}
+
+impl Error for ConfigError {}
+
+/// a single `key = value` line
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfigEntry<'a> {
+    pub line: usize,
+    pub key: &'a str,
+    pub value: &'a str,
+}
+
+impl<'a> ConfigEntry<'a> {
+    pub fn parse_f32(&self) -> Result<f32, ConfigError> {
+        self.value
+            .parse::<f32>()
+            .map_err(|_| ConfigError::new(self.line, format!("`{}` expects a number but got `{}`", self.key, self.value)))
+    }
+
+    pub fn parse_u32(&self) -> Result<u32, ConfigError> {
+        self.value
+            .parse::<u32>()
+            .map_err(|_| ConfigError::new(self.line, format!("`{}` expects a whole number but got `{}`", self.key, self.value)))
+    }
+
+    pub fn unknown_key(&self) -> ConfigError {
+        ConfigError::new(self.line, format!("unknown setting `{}`", self.key))
+    }
+}
+
+pub fn parse_entries(text: &str) -> Result<Vec<ConfigEntry<'_>>, ConfigError> {
+    parse_entries_with(text, strip_comment)
+}
+
+/// the same as `parse_entries` but `strip_comment` decides where each lines comment starts,
+/// for formats that need a way to write a `#` in a value
+pub fn parse_entries_with(text: &str, strip_comment: fn(&str) -> &str) -> Result<Vec<ConfigEntry<'_>>, ConfigError> {
+    let mut entries = Vec::new();
+
+    for (idx, line) in text.lines().enumerate() {
+        let line_number = idx + 1;
+        let line = strip_comment(line).trim();
+
+        if line.is_empty() {
+            continue;
+        }
+
+        let Some((key, value)) = line.split_once('=') else {
+            return Err(ConfigError::new(line_number, format!("expected `key = value` but got `{}`", line)));
+        };
+
+        let key = key.trim();
+        if key.is_empty() {
+            return Err(ConfigError::new(line_number, "missing key before `=`"));
+        }
+
+        entries.push(ConfigEntry {
+            line: line_number,
+            key,
+            value: value.trim(),
+        });
+    }
+
+    Ok(entries)
+}
+
+fn strip_comment(line: &str) -> &str {
+    match line.split_once('#') {
+        Some((before, _)) => before,
+        None => line,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_pairs_and_skips_comments() {
+        let text = "# header\n\nspeed = 10.5\n  name=shork # trailing comment\n";
+        let entries = parse_entries(text).unwrap();
+
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].key, "speed");
+        assert_eq!(entries[0].parse_f32().unwrap(), 10.5);
+        assert_eq!(entries[0].line, 3);
+        assert_eq!(entries[1].key, "name");
+        assert_eq!(entries[1].value, "shork");
+    }
+
+    #[test]
+    fn every_hash_starts_a_comment() {
+        let entries = parse_entries("name = level \\#1 # the first one").unwrap();
+        assert_eq!(entries[0].value, "level \\");
+
+        let entries = parse_entries_with("name = level #1", |line| line).unwrap();
+        assert_eq!(entries[0].value, "level #1");
+    }
+
+    #[test]
+    fn reports_line_of_bad_entry() {
+        let err = parse_entries("a = 1\nnonsense\n").unwrap_err();
+        assert_eq!(err.line, 2);
+
+        let entries = parse_entries("a = fast").unwrap();
+        assert_eq!(entries[0].parse_f32().unwrap_err().line, 1);
+    }
+}
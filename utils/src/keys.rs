@@ -0,0 +1,78 @@
+//! names for keyboard keys so they can be written in settings files. the name is the
+//! keys debug name, so `A`, `Key1`, `Left` or `LeftShift`
+
+use bottomless_pit::input::Key;
+
+/// every key bottomless pit knows about, keep in step with its `Key` enum
+pub const ALL_KEYS: [Key; 74] = [
+    Key::A, Key::B, Key::C, Key::D, Key::E, Key::F, Key::G, Key::H, Key::I,
+    Key::J, Key::K, Key::L, Key::M, Key::N, Key::O, Key::P, Key::Q, Key::R,
+    Key::S, Key::T, Key::U, Key::V, Key::W, Key::X, Key::Y, Key::Z,
+    Key::Key0, Key::Key1, Key::Key2, Key::Key3, Key::Key4,
+    Key::Key5, Key::Key6, Key::Key7, Key::Key8, Key::Key9,
+    Key::Space, Key::Enter, Key::Tab, Key::Backspace, Key::Escape,
+    Key::Left, Key::Right, Key::Up, Key::Down,
+    Key::LeftShift, Key::RightShift, Key::LeftControl, Key::RightControl, Key::LeftAlt, Key::RightAlt,
+    Key::Comma, Key::Period, Key::Minus, Key::Equals, Key::Semicolon, Key::Slash,
+    Key::Backslash, Key::Quote, Key::LeftBracket, Key::RightBracket, Key::Backquote,
+    Key::F1, Key::F2, Key::F3, Key::F4, Key::F5, Key::F6,
+    Key::F7, Key::F8, Key::F9, Key::F10, Key::F11, Key::F12,
+];
+
+pub fn key_name(key: Key) -> String {
+    format!("{:?}", key)
+}
+
+/// case doesnt matter so `space` and `Space` are the same key
+pub fn key_from_name(name: &str) -> Option<Key> {
+    ALL_KEYS.into_iter().find(|k| key_name(*k).eq_ignore_ascii_case(name))
+}
+
+/// the character a key types on a us keyboard, None for keys that dont type anything
+pub fn typed_char(key: Key, shift: bool) -> Option<char> {
+    let (plain, shifted) = match key {
+        Key::A => ('a', 'A'), Key::B => ('b', 'B'), Key::C => ('c', 'C'), Key::D => ('d', 'D'),
+        Key::E => ('e', 'E'), Key::F => ('f', 'F'), Key::G => ('g', 'G'), Key::H => ('h', 'H'),
+        Key::I => ('i', 'I'), Key::J => ('j', 'J'), Key::K => ('k', 'K'), Key::L => ('l', 'L'),
+        Key::M => ('m', 'M'), Key::N => ('n', 'N'), Key::O => ('o', 'O'), Key::P => ('p', 'P'),
+        Key::Q => ('q', 'Q'), Key::R => ('r', 'R'), Key::S => ('s', 'S'), Key::T => ('t', 'T'),
+        Key::U => ('u', 'U'), Key::V => ('v', 'V'), Key::W => ('w', 'W'), Key::X => ('x', 'X'),
+        Key::Y => ('y', 'Y'), Key::Z => ('z', 'Z'),
+        Key::Key0 => ('0', ')'), Key::Key1 => ('1', '!'), Key::Key2 => ('2', '@'), Key::Key3 => ('3', '#'),
+        Key::Key4 => ('4', '$'), Key::Key5 => ('5', '%'), Key::Key6 => ('6', '^'), Key::Key7 => ('7', '&'),
+        Key::Key8 => ('8', '*'), Key::Key9 => ('9', '('),
+        Key::Space => (' ', ' '),
+        Key::Comma => (',', '<'), Key::Period => ('.', '>'), Key::Minus => ('-', '_'), Key::Equals => ('=', '+'),
+        Key::Semicolon => (';', ':'), Key::Slash => ('/', '?'), Key::Backslash => ('\\', '|'),
+        Key::Quote => ('\'', '"'), Key::LeftBracket => ('[', '{'), Key::RightBracket => (']', '}'),
+        Key::Backquote => ('`', '~'),
+        _ => return None,
+    };
+
+    Some(if shift { shifted } else { plain })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_key_comes_back_from_its_name() {
+        for key in ALL_KEYS {
+            assert_eq!(key_from_name(&key_name(key)), Some(key));
+        }
+
+        assert_eq!(key_from_name("leftshift"), Some(Key::LeftShift));
+        assert_eq!(key_from_name("Banana"), None);
+    }
+
+    #[test]
+    fn typing_covers_digits_and_punctuation() {
+        assert_eq!(typed_char(Key::A, true), Some('A'));
+        assert_eq!(typed_char(Key::Key3, false), Some('3'));
+        assert_eq!(typed_char(Key::Key3, true), Some('#'));
+        assert_eq!(typed_char(Key::Quote, false), Some('\''));
+        assert_eq!(typed_char(Key::LeftShift, false), None);
+        assert_eq!(typed_char(Key::F1, true), None);
+    }
+}
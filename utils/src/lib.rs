@@ -1,2 +1,5 @@
 pub mod collision;
+pub mod config;
+pub mod hash;
+pub mod keys;
 pub mod ui;
\ No newline at end of file